@@ -1,7 +1,7 @@
 #![no_std]
 #![no_main]
 
-use core::{cell::RefCell, convert::Infallible};
+use core::cell::RefCell;
 #[allow(unused_imports)]
 use defmt::{debug, error, info, trace, warn};
 use embassy_embedded_hal::shared_bus::blocking::spi::SpiDeviceWithConfig;
@@ -165,10 +165,9 @@ async fn main(spawner: Spawner) -> ! {
     }
 }
 
-fn draw_embedded_graphics_demo<D>(display: &mut D) -> Result<(), core::convert::Infallible>
+fn draw_embedded_graphics_demo<D>(display: &mut D) -> Result<(), D::Error>
 where
     D: embedded_graphics_core::draw_target::DrawTarget<Color = BinaryColor>,
-    Infallible: From<<D as DrawTarget>::Error>,
 {
     // Create styles used by the drawing operations.
     let thin_stroke = PrimitiveStyle::with_stroke(BinaryColor::On, 1);
@@ -0,0 +1,20 @@
+//! Async version of the driver, built on [embedded-hal-async].
+//!
+//! This module mirrors the blocking API of the crate, but every operation that talks to the
+//! controller is an `async fn`. Waiting for the controller to finish a refresh yields to the
+//! executor instead of spinning, so other tasks can run while the display updates.
+//!
+//! The configuration types, [Rotation](crate::Rotation), [Dimensions](crate::Dimensions) and
+//! [DisplayUpdateMode](crate::basic_display::DisplayUpdateMode) are shared with the blocking
+//! driver.
+//!
+//! This module is only available with the `async` feature flag.
+//!
+//! [embedded-hal-async]: https://crates.io/crates/embedded-hal-async
+pub mod basic_display;
+pub mod command;
+pub mod display;
+pub mod interface;
+
+pub use basic_display::BasicDisplay;
+pub use display::Display;
@@ -0,0 +1,377 @@
+//! This module provides the async [BasicDisplay] struct that interfaces with the hardware.
+//!
+//! It is the async counterpart of [crate::basic_display::BasicDisplay], and uses a generic
+//! interface that implements the async [DisplayInterface] and [DisplayCommands] traits.
+use crate::asynch::command::DisplayCommands;
+use crate::asynch::interface::DisplayInterface;
+use crate::basic_display::Dimensions;
+use crate::basic_display::{
+    writes_ram, DisplayUpdateMode, Rotation, UPDATE_LOAD_LUT, UPDATE_LOAD_TEMPERATURE,
+};
+use crate::command::*;
+use crate::config::{Config, InitStep};
+use crate::lut::Lut;
+
+/// A configured async display with a hardware interface
+pub struct BasicDisplay<I>
+where
+    I: DisplayInterface,
+{
+    pub(crate) interface: I,   // The interface for communicating with the display
+    pub(crate) config: Config, // The display configuration
+    asleep: bool,              // Whether the controller is in deep sleep
+    lut: Option<Lut>,          // The custom waveform LUT, if loaded
+}
+
+impl<I> BasicDisplay<I>
+where
+    I: DisplayInterface,
+{
+    /// Create a new display instance from a [DisplayInterface] and a [Config].
+    ///
+    /// The [Config] is created using the [Builder](crate::config::Builder).
+    ///
+    /// # Arguments
+    ///
+    /// * `interface` - The interface for communicating with the display.
+    /// * `config` - The configuration for the display.
+    pub fn new(interface: I, config: Config) -> Self {
+        Self {
+            interface,
+            config,
+            asleep: false,
+            lut: None,
+        }
+    }
+
+    /// Reset the display.
+    ///
+    /// This will perform a hardware reset, followed by a software reset.
+    /// This is useful for waking a controller that has entered deep sleep.
+    ///
+    /// # Arguments
+    ///
+    /// * `delay` - An async delay implementation to use for timing.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), <I as DisplayInterface>::Error>` - Returns Ok on success, or an error if the reset fails.
+    pub async fn reset<D: embedded_hal_async::delay::DelayNs>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<(), <I as DisplayInterface>::Error> {
+        // Perform the hardware reset
+        self.interface.reset_hardware(delay).await?;
+
+        // Perform the software reset
+        self.interface.reset_software().await?;
+
+        // Wait for the display to be ready
+        self.interface.busy_wait().await?;
+
+        // The controller is awake after a hardware reset
+        self.asleep = false;
+
+        // Re-initialize the display
+        self.init().await
+    }
+
+    /// Put the controller into deep sleep.
+    ///
+    /// The controller is woken automatically by the next update, see
+    /// [crate::basic_display::BasicDisplay::sleep].
+    ///
+    /// # Arguments
+    ///
+    /// * `mode` - The deep sleep mode to enter, see [DeepSleepMode].
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), <I as DisplayInterface>::Error>` - Returns Ok on success, or an error if sending the command fails.
+    pub async fn sleep(
+        &mut self,
+        mode: DeepSleepMode,
+    ) -> Result<(), <I as DisplayInterface>::Error> {
+        self.interface.deep_sleep(mode).await?;
+        self.asleep = mode != DeepSleepMode::Normal;
+
+        Ok(())
+    }
+
+    /// Returns true if the controller was put into deep sleep and has not been woken since
+    pub fn is_asleep(&self) -> bool {
+        self.asleep
+    }
+
+    /// Wake the controller if it is in deep sleep, restoring its registers without refreshing
+    /// the display
+    async fn wake_if_asleep(&mut self) -> Result<(), <I as DisplayInterface>::Error> {
+        if !self.asleep {
+            return Ok(());
+        }
+
+        // Deep sleep can only be left through a hardware reset
+        self.interface.wake().await?;
+        self.interface.reset_software().await?;
+        self.interface.busy_wait().await?;
+        self.asleep = false;
+
+        // Restore the registers of the init sequence, leaving the RAM alone and loading the
+        // waveform LUT without refreshing the display
+        for &step in self.config.init_sequence {
+            match step {
+                InitStep::Command(command) if writes_ram(&command) => {}
+                InitStep::LoadLut(_) => self.load_waveform(UPDATE_LOAD_LUT).await?,
+                step => self.init_step(step).await?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Initialize the display controller according to the datasheet.
+    ///
+    /// This sends the same initialization sequence as
+    /// [crate::basic_display::BasicDisplay::init].
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), <I as DisplayInterface>::Error>` - Returns Ok on success, or an error if initialization fails.
+    pub async fn init(&mut self) -> Result<(), <I as DisplayInterface>::Error> {
+        // 3. Send intialization code
+        // 4. Load waveform LUT
+        // Both are part of the init sequence of the panel
+        for &step in self.config.init_sequence {
+            self.init_step(step).await?;
+        }
+
+        // Wait for the display to be ready
+        self.interface.busy_wait().await
+    }
+
+    /// Send a step of the init sequence, filling in the dimensions of the display
+    async fn init_step(&mut self, step: InitStep) -> Result<(), <I as DisplayInterface>::Error> {
+        let Dimensions { rows, cols } = self.config.dimensions;
+        match step {
+            InitStep::Command(command) => self.interface.execute(command).await,
+            InitStep::DriverOutputControl(scanning) => {
+                self.interface
+                    .set_driver_output_control(rows - 1, scanning)
+                    .await
+            }
+            InitStep::RamSize => {
+                self.interface
+                    .set_ram_address_based_on_size(rows, cols)
+                    .await
+            }
+            InitStep::LoadLut(sequence) => self.load_waveform(sequence).await,
+        }
+    }
+
+    /// Load the waveform LUT, see [crate::basic_display::BasicDisplay::init]
+    async fn load_waveform(
+        &mut self,
+        sequence: UpdateSequence,
+    ) -> Result<(), <I as DisplayInterface>::Error> {
+        match self.lut {
+            // Write the custom waveform LUT
+            Some(lut) => self.interface.write_lut(&lut).await?,
+            None => {
+                // Set waveform LUT from OTP
+                self.interface.update_display_option2(sequence).await?;
+                // Run the update sequence
+                self.interface.refresh_display().await?;
+            }
+        }
+
+        // Wait for the display to be ready
+        self.interface.busy_wait().await
+    }
+
+    /// Load a custom waveform LUT in place of the LUT from OTP.
+    ///
+    /// See [crate::basic_display::BasicDisplay::load_lut].
+    ///
+    /// # Arguments
+    ///
+    /// * `lut` - The waveform LUT to use, see [Lut].
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), <I as DisplayInterface>::Error>` - Returns Ok on success, or an error if writing the LUT fails.
+    pub async fn load_lut(&mut self, lut: Lut) -> Result<(), <I as DisplayInterface>::Error> {
+        self.lut = Some(lut);
+
+        // A sleeping controller gets the LUT when it is woken
+        if !self.asleep {
+            self.interface.write_lut(&lut).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Go back to using the waveform LUT from OTP.
+    ///
+    /// The LUT from OTP is loaded again by the next update.
+    pub fn use_otp_lut(&mut self) {
+        self.lut = None;
+    }
+
+    /// Returns the custom waveform LUT, if one is loaded
+    pub fn lut(&self) -> Option<&Lut> {
+        self.lut.as_ref()
+    }
+
+    /// Send the display update sequence for the update mode
+    async fn set_update_mode(
+        &mut self,
+        update_mode: DisplayUpdateMode,
+    ) -> Result<(), <I as DisplayInterface>::Error> {
+        let sequence = match self.lut {
+            Some(_) => update_mode.sequence().without_loading(),
+            None => {
+                if let Some(temperature) = update_mode.temperature() {
+                    self.interface.write_temperature(temperature).await?;
+                }
+                update_mode.sequence()
+            }
+        };
+
+        self.interface.update_display_option2(sequence).await
+    }
+
+    /// Measure the temperature with the selected temperature sensor.
+    ///
+    /// See [crate::basic_display::BasicDisplay::temperature].
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Celsius, <I as DisplayInterface>::Error>` - Returns the temperature on success, or an error if reading it fails.
+    pub async fn temperature(&mut self) -> Result<Celsius, <I as DisplayInterface>::Error> {
+        self.wake_if_asleep().await?;
+
+        // Load the temperature into the register
+        self.interface
+            .update_display_option2(UPDATE_LOAD_TEMPERATURE)
+            .await?;
+        self.interface.refresh_display().await?;
+
+        self.interface.read_temperature().await
+    }
+
+    /// Read the contents of a RAM plane of the controller into a buffer.
+    ///
+    /// The RAM is read from the start, the buffer should be `rows * cols / 8` bytes long to
+    /// read the entire display. This can be used to verify the data written by an update.
+    /// The controller is woken first if it is in deep sleep.
+    ///
+    /// # Arguments
+    ///
+    /// * `plane` - The RAM plane to read, see [RamPlane].
+    /// * `buffer` - The buffer to read the RAM into.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), <I as DisplayInterface>::Error>` - Returns Ok on success, or an error if reading the RAM fails.
+    pub async fn read_ram(
+        &mut self,
+        plane: RamPlane,
+        buffer: &mut [u8],
+    ) -> Result<(), <I as DisplayInterface>::Error> {
+        self.wake_if_asleep().await?;
+
+        // Reset the address
+        self.interface.set_ram_x_count(0).await?;
+        self.interface.set_ram_y_count(0).await?;
+
+        self.interface.read_ram(plane, buffer).await
+    }
+
+    /// Read the status of the controller.
+    ///
+    /// A controller that is missing or not responding reads back the level of the idle data
+    /// line, so the status can be used to check for the controller at boot.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Status, <I as DisplayInterface>::Error>` - Returns the status on success, or an error if reading it fails.
+    pub async fn status(&mut self) -> Result<Status, <I as DisplayInterface>::Error> {
+        self.interface.read_status().await
+    }
+
+    /// Returns a mutable reference to the interface, to send commands not covered by the display.
+    ///
+    /// Commands that change the RAM window, data entry mode or waveform can break later updates.
+    pub fn interface_mut(&mut self) -> &mut I {
+        &mut self.interface
+    }
+
+    /// Update the display contents by writing the supplied buffers to the controller.
+    ///
+    /// This behaves like [crate::basic_display::BasicDisplay::update], but yields to the
+    /// executor while the controller is busy refreshing the panel.
+    ///
+    /// # Arguments
+    ///
+    /// * `bw_buffer` - an optional slice of bytes representing the black and white pixel data.
+    ///   If `None`, the black and white RAM will not be updated.
+    /// * `red_buffer` - An optional slice of bytes representing the red pixel data.
+    ///   If `None`, the red RAM will not be updated.
+    /// * `update_mode` - The kind of update to do, see [DisplayUpdateMode]
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), <I as DisplayInterface>::Error>` - Returns `Ok(())` on success, or an error
+    ///   if writing to the RAM or refreshing the display fails.
+    pub async fn update(
+        &mut self,
+        bw_buffer: Option<&[u8]>,
+        red_buffer: Option<&[u8]>,
+        update_mode: DisplayUpdateMode,
+    ) -> Result<(), <I as DisplayInterface>::Error> {
+        self.wake_if_asleep().await?;
+
+        // Write the black and white RAM if provided
+        if let Some(buffer) = bw_buffer {
+            // Reset the address
+            self.interface.set_ram_x_count(0).await?;
+            self.interface.set_ram_y_count(0).await?;
+
+            // Copy the data
+            self.interface.write_ram_black_and_white(buffer).await?;
+        }
+
+        // Write the red RAM if provided
+        if let Some(buffer) = red_buffer {
+            // Reset the address
+            self.interface.set_ram_x_count(0).await?;
+            self.interface.set_ram_y_count(0).await?;
+
+            // Copy the data
+            self.interface.write_ram_red(buffer).await?;
+        }
+
+        // Set the update mode
+        self.set_update_mode(update_mode).await?;
+
+        // Refresh the display
+        self.interface.refresh_display().await?;
+
+        Ok(())
+    }
+
+    /// Return the number of rows the display has
+    pub fn rows(&self) -> u16 {
+        self.config.dimensions.rows
+    }
+
+    /// Return the number of cols the display has
+    pub fn cols(&self) -> u16 {
+        self.config.dimensions.cols
+    }
+
+    /// Returns the rotation the display was configured with
+    pub fn rotation(&self) -> Rotation {
+        self.config.rotation
+    }
+}
@@ -0,0 +1,286 @@
+//! This module defines the async commands to the [BasicDisplay](crate::asynch::BasicDisplay).
+//!
+//! The valid options to the commands are shared with the blocking driver and live in
+//! [crate::command].
+use crate::asynch::interface::DisplayInterface;
+use crate::command::*;
+use crate::lut::Lut;
+use crate::voltage::{GateVoltage, SourceVoltage, VcomVoltage};
+
+/// The async commands implemented on the display
+///
+/// Commands report errors using the [DisplayInterface::Error] of the interface they are sent
+/// over. Every command is sent with [DisplayInterface::execute], the same as the blocking
+/// [DisplayCommands](crate::command::DisplayCommands), and the commands are implemented for any
+/// [DisplayInterface], including custom transports.
+#[allow(async_fn_in_trait)]
+pub trait DisplayCommands: DisplayInterface {
+    /// Set the MUX of gate lines, scanning sequence and direction
+    async fn set_driver_output_control(
+        &mut self,
+        max_gate_lines: u16,
+        scanning_sequence_and_direction: u8,
+    ) -> Result<(), Self::Error> {
+        self.execute(Command::DriverOutputControl(
+            max_gate_lines,
+            scanning_sequence_and_direction,
+        ))
+        .await
+    }
+
+    async fn set_driver_output_control_from_width(
+        &mut self,
+        width: u16,
+    ) -> Result<(), Self::Error> {
+        // This command set is based on the example code for the STM32 from here:
+        // https://www.good-display.com/product/457.html
+        self.execute(Command::DriverOutputControl(width - 1, 0x02))
+            .await
+    }
+
+    /// Define the data entry mode settings
+    async fn set_data_entry_mode(
+        &mut self,
+        data_entry_mode: DataEntryMode,
+        increment_axis: IncrementAxis,
+    ) -> Result<(), Self::Error> {
+        self.execute(Command::DataEntryMode(data_entry_mode, increment_axis))
+            .await
+    }
+
+    /// Write data to the black and white RAM buffer
+    async fn write_ram_black_and_white(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        self.execute(Command::WriteRamBlackAndWhite(data)).await
+    }
+
+    /// Write data to the red RAM buffer
+    async fn write_ram_red(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        self.execute(Command::WriteRamRed(data)).await
+    }
+
+    /// Fill the red RAM buffer with a single value
+    async fn auto_write_ram_red_regular_pattern(&mut self, value: u8) -> Result<(), Self::Error> {
+        self.execute(Command::AutoWriteRedRamRegularPattern(value))
+            .await
+    }
+
+    /// Fill the black and white RAM buffer with a single value
+    async fn auto_write_ram_black_and_white_regular_pattern(
+        &mut self,
+        value: u8,
+    ) -> Result<(), Self::Error> {
+        self.execute(Command::AutoWriteBlackAndWhiteRamRegularPattern(value))
+            .await
+    }
+
+    /// Set the current X axis count
+    async fn set_ram_x_count(&mut self, offset: u16) -> Result<(), Self::Error> {
+        self.execute(Command::RamXCount(offset)).await
+    }
+
+    /// Set the current Y axis count
+    async fn set_ram_y_count(&mut self, offset: u16) -> Result<(), Self::Error> {
+        self.execute(Command::RamYCount(offset)).await
+    }
+
+    async fn refresh_display(&mut self) -> Result<(), Self::Error> {
+        // Busy waits until the refresh is done
+        self.execute(Command::UpdateDisplay).await
+    }
+
+    /// Specify the start/end positions of the window address in the X direction by an address unit
+    /// for RAM.
+    ///
+    /// # Note
+    /// Start any end values are 10-bit, bit ranges 11-16 will be discarded.
+    async fn set_ram_x_address(&mut self, start: u16, end: u16) -> Result<(), Self::Error> {
+        self.execute(Command::RamXAddress(start, end)).await
+    }
+
+    /// Specify the start/end positions of the window address in the Y direction by an address unit
+    /// for RAM.
+    ///
+    /// # Note
+    /// Start any end values are 10-bit, bit ranges 11-16 will be discarded.
+    async fn set_ram_y_address(&mut self, start: u16, end: u16) -> Result<(), Self::Error> {
+        self.execute(Command::RamYAddress(start, end)).await
+    }
+
+    /// Set the start and end RAM addresses for both X and Y based on the display dimentions given
+    async fn set_ram_address_based_on_size(
+        &mut self,
+        width: u16,
+        height: u16,
+    ) -> Result<(), Self::Error> {
+        self.set_ram_x_address(0, height - 1).await?;
+        self.set_ram_y_address(0, width - 1).await?;
+
+        Ok(())
+    }
+
+    // No operation instruction, does nothing.
+    // It can be used to terminate Frame Memory Write or Read commands
+    async fn nop(&mut self) -> Result<(), Self::Error> {
+        self.execute(Command::Nop).await
+    }
+
+    /// Set the gate driving voltage, see [GateVoltage] for the valid values
+    async fn set_gate_driving_voltage(&mut self, voltage: GateVoltage) -> Result<(), Self::Error> {
+        self.execute(Command::GateDrivingVoltage(voltage)).await
+    }
+
+    /// Set the source driving voltage, see [SourceVoltage] for the valid values
+    ///
+    /// # NOTE
+    /// VSH1 must be larger than or equal to VSH2, this is validated by [SourceVoltage]
+    async fn set_source_driving_voltage(
+        &mut self,
+        voltage: SourceVoltage,
+    ) -> Result<(), Self::Error> {
+        self.execute(Command::SourceDrivingVoltage(voltage)).await
+    }
+
+    /// Write the VCOM register, see [VcomVoltage] for the valid values
+    async fn write_vcom(&mut self, voltage: VcomVoltage) -> Result<(), Self::Error> {
+        self.execute(Command::WriteVcom(voltage)).await
+    }
+
+    /// Read the display options programmed into OTP
+    async fn read_otp_display_option(&mut self) -> Result<OtpDisplayOption, Self::Error> {
+        let mut data = [0u8; OtpDisplayOption::LEN];
+        self.read_command(Command::ReadOtpDisplayOption.opcode(), &mut [], &mut data)
+            .await?;
+
+        Ok(OtpDisplayOption::from_bytes(&data))
+    }
+
+    /// Read RAM from the current RAM address counters.
+    /// The counters advance as with a write, so consecutive reads continue where the last ended.
+    async fn read_ram(&mut self, plane: RamPlane, data: &mut [u8]) -> Result<(), Self::Error> {
+        // Select the RAM to read
+        self.execute(Command::ReadRamOption(plane)).await?;
+
+        // The first byte read after the command is a dummy byte
+        self.read_command(Command::ReadRam.opcode(), &mut [0u8], data)
+            .await
+    }
+
+    /// Read the status bits of the controller
+    async fn read_status(&mut self) -> Result<Status, Self::Error> {
+        let mut data = [0u8];
+        self.read_command(Command::ReadStatus.opcode(), &mut [], &mut data)
+            .await?;
+
+        Ok(Status::from_byte(data[0]))
+    }
+
+    /// Read the user ID programmed into OTP
+    async fn read_user_id(&mut self) -> Result<[u8; USER_ID_LEN], Self::Error> {
+        let mut data = [0u8; USER_ID_LEN];
+        self.read_command(Command::ReadUserId.opcode(), &mut [], &mut data)
+            .await?;
+
+        Ok(data)
+    }
+
+    /// Set RAM content options for update display command.
+    async fn update_display_option1(
+        &mut self,
+        black_and_white_option: RamOption,
+        red_option: RamOption,
+    ) -> Result<(), Self::Error> {
+        self.execute(Command::UpdateDisplayOption1(
+            black_and_white_option,
+            red_option,
+        ))
+        .await
+    }
+
+    /// Set display update sequence option
+    /// See [UpdateSequence] for the steps that can be run
+    async fn update_display_option2(
+        &mut self,
+        sequence: UpdateSequence,
+    ) -> Result<(), Self::Error> {
+        self.execute(Command::UpdateDisplayOption2(sequence)).await
+    }
+
+    /// Write a waveform LUT to the controller, replacing the LUT loaded from OTP
+    async fn write_lut(&mut self, lut: &Lut) -> Result<(), Self::Error> {
+        self.execute(Command::WriteLut(&lut.to_bytes())).await
+    }
+
+    /// Perform a hardware reset
+    async fn reset_hardware<D: embedded_hal_async::delay::DelayNs>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<(), Self::Error> {
+        // Toggle the reset pin
+        self.reset(delay).await
+    }
+
+    /// Perform a software reset.
+    /// This resets all parameters except deep sleep mode to their default values.
+    /// RAM content is not affected.
+    /// BUSY will be high while reset is in progress
+    async fn reset_software(&mut self) -> Result<(), Self::Error> {
+        // Busy waits until the soft reset is over
+        self.execute(Command::SoftwareReset).await
+    }
+
+    /// Select border waveform for VBD
+    async fn set_border_waveform_control(
+        &mut self,
+        vdb_option: WaveformVDBOption,
+        fixed_level_setting: VDBFixedLevelSetting,
+        transition_setting: VDBGSTransitionSetting,
+    ) -> Result<(), Self::Error> {
+        self.execute(Command::BorderWaveformControl(
+            vdb_option,
+            fixed_level_setting,
+            transition_setting,
+        ))
+        .await
+    }
+
+    /// Specify which temperature sensor the display uses
+    async fn set_temperature_sensor(
+        &mut self,
+        sensor: TemperatureSensor,
+    ) -> Result<(), Self::Error> {
+        self.execute(Command::TemperatureSensor(sensor)).await
+    }
+
+    /// Write the temperature register, used to select the waveform when loading the LUT
+    async fn write_temperature(&mut self, temperature: Celsius) -> Result<(), Self::Error> {
+        self.execute(Command::WriteTemperature(temperature)).await
+    }
+
+    /// Read the temperature register.
+    /// The register is only updated when the display update sequence loads the temperature.
+    async fn read_temperature(&mut self) -> Result<Celsius, Self::Error> {
+        let mut register = [0u8; 2];
+        self.read_command(Command::ReadTemperature.opcode(), &mut [], &mut register)
+            .await?;
+        Ok(Celsius::from_register(register))
+    }
+
+    /// Control the inrush current for the booster
+    async fn set_booster_soft_start_control(
+        &mut self,
+        inrush: BoosterInrush,
+    ) -> Result<(), Self::Error> {
+        self.execute(Command::BoosterSoftStartControl(inrush)).await
+    }
+
+    /// Enter or leave deep sleep mode.
+    /// The busy pin stays high while the controller is in deep sleep, and the controller
+    /// can only be woken by a hardware reset.
+    async fn deep_sleep(&mut self, mode: DeepSleepMode) -> Result<(), Self::Error> {
+        // Does not wait for the busy pin, it will not go low again while sleeping
+        self.execute(Command::DeepSleepMode(mode)).await
+    }
+}
+
+/// The commands are sent over any interface to the controller
+impl<I> DisplayCommands for I where I: DisplayInterface {}
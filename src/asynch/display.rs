@@ -0,0 +1,196 @@
+//! This module provides the async [Display] struct for managing
+//! a black and white display with graphics capabilities.
+//!
+//! It is the async counterpart of [crate::display::Display]. Drawing into the buffer is
+//! synchronous, only sending the buffer to the controller with [Display::update] is async.
+//! Because of this the `auto_update` option of the [Config] has no effect on this display,
+//! [Display::update] must be called to show the buffer contents.
+//!
+//! [Config]: crate::config::Config
+use crate::asynch::basic_display::BasicDisplay;
+use crate::asynch::interface::DisplayInterface;
+use crate::basic_display::{DisplayUpdateMode, Rotation};
+use crate::config;
+use crate::display::rotation;
+
+#[cfg(feature = "graphics")]
+use embedded_graphics_core::{pixelcolor::BinaryColor, prelude::*};
+
+#[cfg(feature = "defmt")]
+use defmt::*;
+
+/// An async display that holds buffers for drawing into and updating the display.
+pub struct Display<'a, I>
+where
+    I: DisplayInterface,
+{
+    display: BasicDisplay<I>, // The underlying display interface
+    bw_buffer: &'a mut [u8],  // The buffer for black and white pixel data
+}
+
+impl<'a, I> Display<'a, I>
+where
+    I: DisplayInterface,
+{
+    /// Creates a new [Display] instance.
+    ///
+    /// # Parameters
+    ///
+    /// - `interface`: An instance of the interface type `I` that will be used for communication
+    ///   with the display hardware, such as the async [Interface4Pin].
+    /// - `bw_buffer`: A mutable reference to a byte slice (`&'a mut [u8]`) that serves as the
+    ///   buffer for storing black-and-white pixel data. This buffer must be large enough to hold
+    ///   the pixel data for the display.
+    /// - `config`: An instance of [Config] that contains the configuration settings for
+    ///   the display.
+    ///
+    /// [Interface4Pin]: crate::asynch::interface::Interface4Pin
+    /// [Config]: crate::config::Config
+    pub fn new(interface: I, bw_buffer: &'a mut [u8], config: config::Config) -> Self {
+        // First create a basic display
+        let d = BasicDisplay::new(interface, config);
+
+        // Promote the basic display to a Display
+        Display::from_basic_display(d, bw_buffer)
+    }
+
+    /// Promote a [BasicDisplay] to a [Display].
+    ///
+    /// The black and white buffer must be provided. It should be of length
+    /// `rows * cols / 8`, where `rows` and `cols` are the dimensions of the display.
+    pub fn from_basic_display(display: BasicDisplay<I>, bw_buffer: &'a mut [u8]) -> Self {
+        Display { display, bw_buffer }
+    }
+
+    /// Update the display by writing the buffer to the controller.
+    ///
+    /// # Arguments
+    ///
+    /// * `mode` - The kind of update to perform, see [DisplayUpdateMode] for details.
+    pub async fn update(
+        &mut self,
+        mode: DisplayUpdateMode,
+    ) -> Result<(), <I as DisplayInterface>::Error> {
+        self.display.update(Some(self.bw_buffer), None, mode).await
+    }
+
+    #[cfg(feature = "graphics")]
+    /// Clear the buffer, filling it with a single color given by the [BinaryColor] type.
+    ///
+    /// Unlike the blocking display this never refreshes the panel, call [Display::update]
+    /// afterwards to show the cleared buffer.
+    ///
+    /// [BinaryColor]: https://docs.rs/embedded-graphics-core/0.4.0/embedded_graphics_core/pixelcolor/enum.BinaryColor.html
+    pub fn clear(&mut self, color: BinaryColor) {
+        // Figure out the fill value
+        let fill_value: u8 = match color {
+            BinaryColor::On => 0x00,
+            BinaryColor::Off => 0xFF,
+        };
+
+        self.bw_buffer.fill(fill_value);
+    }
+
+    /// Set a pixel at the specified coordinates to the given color.
+    ///
+    /// This method updates the buffer to reflect the color of the pixel at the
+    /// specified `(x, y)` coordinates, taking into account the current rotation
+    /// of the display.
+    ///
+    /// [BinaryColor]: https://docs.rs/embedded-graphics-core/0.4.0/embedded_graphics_core/pixelcolor/enum.BinaryColor.html
+    pub fn set_pixel(&mut self, x: u32, y: u32, color: BinaryColor) {
+        // Find out the buffer index and bit value
+        let (index, bit) = rotation(
+            x,
+            y,
+            self.cols() as u32,
+            self.rows() as u32,
+            self.rotation(),
+        );
+        let index = index as usize;
+
+        #[cfg(feature = "defmt")]
+        trace!("Setting pixel on index {} to {}", index, bit);
+
+        // Set the value in the display buffer
+        match color {
+            BinaryColor::On => {
+                self.bw_buffer[index] &= !bit;
+            }
+            BinaryColor::Off => {
+                self.bw_buffer[index] |= bit;
+            }
+        }
+    }
+}
+
+impl<'a, I> core::ops::Deref for Display<'a, I>
+where
+    I: DisplayInterface,
+{
+    type Target = BasicDisplay<I>;
+
+    /// Dereference to access the underlying [BasicDisplay] instance.
+    fn deref(&self) -> &BasicDisplay<I> {
+        &self.display
+    }
+}
+
+impl<'a, I> core::ops::DerefMut for Display<'a, I>
+where
+    I: DisplayInterface,
+{
+    /// Mutably dereference to access the underlying [BasicDisplay] instance.
+    fn deref_mut(&mut self) -> &mut BasicDisplay<I> {
+        &mut self.display
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl<'a, I> DrawTarget for Display<'a, I>
+where
+    I: DisplayInterface,
+{
+    type Color = BinaryColor;
+    type Error = core::convert::Infallible;
+
+    /// Draw pixels from an iterator into the display buffer.
+    ///
+    /// The panel is not refreshed, call [Display::update] to show the changes.
+    fn draw_iter<Iter>(&mut self, pixels: Iter) -> Result<(), Self::Error>
+    where
+        Iter: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let size = self.size();
+
+        // Draw the image pixel by pixel
+        for Pixel(Point { x, y }, color) in pixels {
+            let x = x as u32;
+            let y = y as u32;
+
+            if x < size.width && y < size.height {
+                self.set_pixel(x, y, color);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl<'a, I> OriginDimensions for Display<'a, I>
+where
+    I: DisplayInterface,
+{
+    /// Get the size of the display in pixels, taking the rotation into account.
+    fn size(&self) -> Size {
+        match self.rotation() {
+            Rotation::Rotate0 | Rotation::Rotate180 => {
+                Size::new(self.cols().into(), self.rows().into())
+            }
+            Rotation::Rotate90 | Rotation::Rotate270 => {
+                Size::new(self.rows().into(), self.cols().into())
+            }
+        }
+    }
+}
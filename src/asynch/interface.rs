@@ -0,0 +1,604 @@
+//! Async hardware interface to the display
+//!
+//! This module implements the functions required to communicate and interface
+//! with the SSD1677 display controller from an async context. It provides a trait for
+//! display communication and a specific implementation for 4-pin SPI mode.
+use core::future::{poll_fn, Future};
+use core::pin::{pin, Pin};
+use core::task::{Context, Poll};
+
+use crate::command::{Command, MAX_PARAMETERS_LEN};
+use crate::error::SSD1677Error;
+use crate::interface::{
+    ClockTimeout, NoResetDelay, NoTimeout, DEFAULT_MAX_TRANSFER_LEN, RESET_DELAY_MS,
+};
+use embedded_hal_async::spi::Operation;
+
+/// Trait implemented by async displays for core functionality
+///
+/// This is the async counterpart of [DisplayInterface](crate::interface::DisplayInterface).
+/// Implementing this trait allows for sending commands, data, resetting the controller,
+/// and waiting for the controller to be ready without blocking the executor.
+///
+/// # Associated Types
+///
+/// * `Error` - The type of error that can occur during communication.
+#[allow(async_fn_in_trait)]
+pub trait DisplayInterface {
+    type Error: core::fmt::Debug;
+
+    /// Send a command to the display controller
+    async fn send_command(&mut self, command: u8) -> Result<(), Self::Error>;
+
+    /// Send data for a command
+    async fn send_data(&mut self, data: &[u8]) -> Result<(), Self::Error>;
+
+    /// Send data for a command without waiting for the controller afterwards.
+    ///
+    /// See [crate::interface::DisplayInterface::send_data_without_busy_wait].
+    async fn send_data_without_busy_wait(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        self.send_data(data).await
+    }
+
+    /// Send a command and read the data the controller returns for it.
+    ///
+    /// See [crate::interface::DisplayInterface::read_command], interfaces that can not read from
+    /// the controller must return an error.
+    async fn read_command(
+        &mut self,
+        command: u8,
+        dummy: &mut [u8],
+        data: &mut [u8],
+    ) -> Result<(), Self::Error>;
+
+    /// Reset the controller
+    async fn reset<D: embedded_hal_async::delay::DelayNs>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<(), Self::Error>;
+
+    /// Wake the controller from deep sleep.
+    ///
+    /// See [crate::interface::DisplayInterface::wake], the async [Interface4Pin] is given its
+    /// delay with [Interface4Pin::with_reset_delay]. Interfaces that can not reset the
+    /// controller must return an error.
+    async fn wake(&mut self) -> Result<(), Self::Error>;
+
+    /// Wait for the controller to indicate that it is not busy.
+    ///
+    /// This method completes once the display controller is ready to accept new commands
+    /// or data, yielding to the executor while the controller is busy.
+    async fn busy_wait(&mut self) -> Result<(), Self::Error>;
+
+    /// Send a [Command] with its parameters to the controller.
+    ///
+    /// See [crate::interface::DisplayInterface::execute].
+    async fn execute(&mut self, command: Command<'_>) -> Result<(), Self::Error> {
+        let mut buf = [0u8; MAX_PARAMETERS_LEN];
+        let (opcode, parameters) = command.encode(&mut buf);
+
+        self.send_command(opcode).await?;
+        match command {
+            // Do not wait for the busy pin, it will not go low again while sleeping
+            Command::DeepSleepMode(_) => self.send_data_without_busy_wait(parameters).await?,
+            _ if !parameters.is_empty() => self.send_data(parameters).await?,
+            _ => {}
+        }
+        if let Command::SoftwareReset | Command::UpdateDisplay = command {
+            self.busy_wait().await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Strategy used by the async [Interface4Pin] to wait for the controller's busy pin.
+///
+/// Two strategies are provided:
+/// * [PollingBusyPin] polls an [InputPin](embedded_hal::digital::InputPin), yielding to the
+///   executor between every poll. The CPU keeps running while the controller is busy.
+/// * [InterruptBusyPin] awaits the falling edge of a pin implementing
+///   [Wait](embedded_hal_async::digital::Wait). The executor can put the MCU to sleep until the
+///   interrupt for the pin fires, which is preferable for battery powered devices.
+#[allow(async_fn_in_trait)]
+pub trait BusyPin {
+    /// The error returned when the busy pin can not be read
+    type Error: core::fmt::Debug;
+
+    /// Wait until the busy pin indicates that the controller is idle
+    async fn wait_until_idle(&mut self) -> Result<(), Self::Error>;
+}
+
+/// Busy pin that is polled while the controller is busy, see [BusyPin].
+pub struct PollingBusyPin<IN>(pub IN);
+
+/// Busy pin that is awaited by interrupt while the controller is busy, see [BusyPin].
+pub struct InterruptBusyPin<IN>(pub IN);
+
+impl<IN> BusyPin for PollingBusyPin<IN>
+where
+    IN: embedded_hal::digital::InputPin,
+{
+    type Error = IN::Error;
+
+    async fn wait_until_idle(&mut self) -> Result<(), Self::Error> {
+        // Give the executor a chance to run other tasks between every poll of the pin
+        while self.0.is_high()? {
+            YieldNow { yielded: false }.await;
+        }
+
+        Ok(())
+    }
+}
+
+impl<IN> BusyPin for InterruptBusyPin<IN>
+where
+    IN: embedded_hal_async::digital::Wait,
+{
+    type Error = IN::Error;
+
+    async fn wait_until_idle(&mut self) -> Result<(), Self::Error> {
+        // Returns immediately if the pin is already low
+        self.0.wait_for_low().await
+    }
+}
+
+/// Timer bounding how long the async [DisplayInterface::busy_wait] may wait.
+///
+/// The async counterpart of [BusyTimer](crate::interface::BusyTimer). The wait on the busy pin
+/// is raced against [BusyTimer::timeout], and fails if the timeout completes first.
+///
+/// The crate provides the following timers:
+/// * [NoTimeout] never completes, the interface waits forever. This is the default.
+/// * [DelayTimeout] waits using an async [DelayNs](embedded_hal_async::delay::DelayNs).
+/// * [ClockTimeout] reads elapsed time from a caller-provided clock, yielding to the executor
+///   between reads.
+#[allow(async_fn_in_trait)]
+pub trait BusyTimer {
+    /// Complete once the timeout has elapsed, timed from the start of every call
+    async fn timeout(&mut self);
+}
+
+impl BusyTimer for NoTimeout {
+    async fn timeout(&mut self) {
+        core::future::pending().await
+    }
+}
+
+impl<C> BusyTimer for ClockTimeout<C>
+where
+    C: FnMut() -> u32,
+{
+    async fn timeout(&mut self) {
+        crate::interface::BusyTimer::start(self);
+        while !crate::interface::BusyTimer::expired(self) {
+            YieldNow { yielded: false }.await;
+        }
+    }
+}
+
+/// A [BusyTimer] driven by an async [DelayNs](embedded_hal_async::delay::DelayNs).
+pub struct DelayTimeout<D> {
+    delay: D,
+    timeout_ms: u32,
+}
+
+impl<D> DelayTimeout<D>
+where
+    D: embedded_hal_async::delay::DelayNs,
+{
+    /// Create a new `DelayTimeout`.
+    ///
+    /// # Arguments
+    ///
+    /// * `delay` - The delay used to wait for the timeout.
+    /// * `timeout_ms` - How long to wait for the controller before timing out, in milliseconds.
+    pub fn new(delay: D, timeout_ms: u32) -> Self {
+        Self { delay, timeout_ms }
+    }
+}
+
+impl<D> BusyTimer for DelayTimeout<D>
+where
+    D: embedded_hal_async::delay::DelayNs,
+{
+    async fn timeout(&mut self) {
+        self.delay.delay_ms(self.timeout_ms).await;
+    }
+}
+
+/// Delay stored in the async [Interface4Pin] to time the hardware reset of
+/// [DisplayInterface::wake].
+///
+/// Implemented for every async [DelayNs](embedded_hal_async::delay::DelayNs), and by
+/// [NoResetDelay] for interfaces without a delay of their own.
+#[allow(async_fn_in_trait)]
+pub trait ResetDelay {
+    /// Returns true if the delay can time a reset
+    fn can_reset(&self) -> bool;
+
+    /// Wait for `ms` milliseconds
+    async fn wait_ms(&mut self, ms: u32);
+}
+
+impl ResetDelay for NoResetDelay {
+    fn can_reset(&self) -> bool {
+        false
+    }
+
+    async fn wait_ms(&mut self, _ms: u32) {}
+}
+
+impl<D> ResetDelay for D
+where
+    D: embedded_hal_async::delay::DelayNs,
+{
+    fn can_reset(&self) -> bool {
+        true
+    }
+
+    async fn wait_ms(&mut self, ms: u32) {
+        self.delay_ms(ms).await;
+    }
+}
+
+/// Async interface to the SSD1677 driver operating in 4pin SPI mode
+///
+/// This is the async counterpart of [Interface4Pin](crate::interface::Interface4Pin), using an
+/// [embedded_hal_async::spi::SpiDevice] for communication with the controller.
+///
+/// How the busy pin is waited on is decided by the constructor, see [BusyPin]. By default the
+/// interface waits forever, use [Interface4Pin::with_busy_timeout] to bound the wait. To wake
+/// the controller from deep sleep the interface needs a delay, set with
+/// [Interface4Pin::with_reset_delay].
+///
+/// All methods report failures as an [SSD1677Error], with errors of the busy pin reported
+/// separately from those of the output pins.
+///
+/// Large writes are split into transfers of at most [DEFAULT_MAX_TRANSFER_LEN] bytes, use
+/// [Interface4Pin::with_max_transfer_len] to match the limit of the SPI driver.
+pub struct Interface4Pin<SPI, OUT, BUSY, T = NoTimeout, R = NoResetDelay> {
+    /// The SpiDevice to communicate with the display
+    spi: SPI,
+    /// Data / Command pin, 0=command, 1=data
+    data_command_pin: OUT,
+    /// The reset pin for the display
+    pub reset_pin: OUT,
+    /// The pin from the controller indicating busy
+    busy_pin: BUSY,
+    /// The timer bounding how long to wait on the busy pin
+    busy_timer: T,
+    /// The delay timing the reset when waking the controller
+    reset_delay: R,
+    /// The maximum number of bytes per SPI transfer
+    max_transfer_len: usize,
+    /// Whether the data/command pin is only set once for all transfers of a write
+    hold_data_command: bool,
+}
+
+impl<SPI, OUT, IN> Interface4Pin<SPI, OUT, PollingBusyPin<IN>>
+where
+    SPI: embedded_hal_async::spi::SpiDevice,
+    OUT: embedded_hal::digital::OutputPin,
+    IN: embedded_hal::digital::InputPin,
+{
+    /// Create a new async `Interface4Pin` that polls the busy pin.
+    ///
+    /// # Arguments
+    ///
+    /// * `spi` - The async SPI device used for communication with the display.
+    /// * `data_command_pin` - The pin used to indicate whether the operation is a command or data.
+    /// * `reset_pin` - The pin used to reset the display.
+    /// * `busy_pin` - The pin used to check if the display is busy.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - A new instance of `Interface4Pin`.
+    pub fn new(spi: SPI, data_command_pin: OUT, reset_pin: OUT, busy_pin: IN) -> Self {
+        Self {
+            spi,
+            data_command_pin,
+            reset_pin,
+            busy_pin: PollingBusyPin(busy_pin),
+            busy_timer: NoTimeout,
+            reset_delay: NoResetDelay,
+            max_transfer_len: DEFAULT_MAX_TRANSFER_LEN,
+            hold_data_command: true,
+        }
+    }
+}
+
+impl<SPI, OUT, IN> Interface4Pin<SPI, OUT, InterruptBusyPin<IN>>
+where
+    SPI: embedded_hal_async::spi::SpiDevice,
+    OUT: embedded_hal::digital::OutputPin,
+    IN: embedded_hal_async::digital::Wait,
+{
+    /// Create a new async `Interface4Pin` that waits on the busy pin by interrupt.
+    ///
+    /// Waiting for the controller becomes `busy_pin.wait_for_low().await`, allowing the MCU to
+    /// sleep during the 1-3 seconds a refresh takes.
+    ///
+    /// # Arguments
+    ///
+    /// * `spi` - The async SPI device used for communication with the display.
+    /// * `data_command_pin` - The pin used to indicate whether the operation is a command or data.
+    /// * `reset_pin` - The pin used to reset the display.
+    /// * `busy_pin` - The pin used to wait for the display to no longer be busy, such as an
+    ///   `ExtiInput` on embassy.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - A new instance of `Interface4Pin`.
+    pub fn new_with_interrupt(
+        spi: SPI,
+        data_command_pin: OUT,
+        reset_pin: OUT,
+        busy_pin: IN,
+    ) -> Self {
+        Self {
+            spi,
+            data_command_pin,
+            reset_pin,
+            busy_pin: InterruptBusyPin(busy_pin),
+            busy_timer: NoTimeout,
+            reset_delay: NoResetDelay,
+            max_transfer_len: DEFAULT_MAX_TRANSFER_LEN,
+            hold_data_command: true,
+        }
+    }
+}
+
+impl<SPI, OUT, BUSY, T, R> Interface4Pin<SPI, OUT, BUSY, T, R>
+where
+    SPI: embedded_hal_async::spi::SpiDevice,
+    OUT: embedded_hal::digital::OutputPin,
+    BUSY: BusyPin,
+    T: BusyTimer,
+    R: ResetDelay,
+{
+    /// Bound how long the interface waits for the controller to be ready.
+    ///
+    /// See [Interface4Pin::with_busy_timeout](crate::interface::Interface4Pin::with_busy_timeout),
+    /// the wait fails with [SSD1677Error::BusyTimeout] when the timeout completes first.
+    ///
+    /// # Arguments
+    ///
+    /// * `busy_timer` - The timer to use, such as a [DelayTimeout] or a [ClockTimeout].
+    pub fn with_busy_timeout<T2: BusyTimer>(
+        self,
+        busy_timer: T2,
+    ) -> Interface4Pin<SPI, OUT, BUSY, T2, R> {
+        Interface4Pin {
+            spi: self.spi,
+            data_command_pin: self.data_command_pin,
+            reset_pin: self.reset_pin,
+            busy_pin: self.busy_pin,
+            busy_timer,
+            reset_delay: self.reset_delay,
+            max_transfer_len: self.max_transfer_len,
+            hold_data_command: self.hold_data_command,
+        }
+    }
+
+    /// Set the delay used to time the hardware reset that wakes the controller from deep sleep.
+    ///
+    /// See [Interface4Pin::with_reset_delay](crate::interface::Interface4Pin::with_reset_delay).
+    ///
+    /// # Arguments
+    ///
+    /// * `reset_delay` - The delay to use, any async
+    ///   [DelayNs](embedded_hal_async::delay::DelayNs).
+    pub fn with_reset_delay<R2: ResetDelay>(
+        self,
+        reset_delay: R2,
+    ) -> Interface4Pin<SPI, OUT, BUSY, T, R2> {
+        Interface4Pin {
+            spi: self.spi,
+            data_command_pin: self.data_command_pin,
+            reset_pin: self.reset_pin,
+            busy_pin: self.busy_pin,
+            busy_timer: self.busy_timer,
+            reset_delay,
+            max_transfer_len: self.max_transfer_len,
+            hold_data_command: self.hold_data_command,
+        }
+    }
+
+    /// Set the maximum number of bytes sent in a single SPI transfer.
+    ///
+    /// See [Interface4Pin::with_max_transfer_len](crate::interface::Interface4Pin::with_max_transfer_len).
+    ///
+    /// # Arguments
+    ///
+    /// * `max_transfer_len` - The maximum transfer length in bytes, a length of 0 is treated as 1.
+    pub fn with_max_transfer_len(mut self, max_transfer_len: usize) -> Self {
+        self.max_transfer_len = max_transfer_len.max(1);
+        self
+    }
+
+    /// Set whether the data/command pin is held across the transfers of a split write.
+    ///
+    /// See [Interface4Pin::with_data_command_held](crate::interface::Interface4Pin::with_data_command_held).
+    ///
+    /// # Arguments
+    ///
+    /// * `hold` - Whether to hold the data/command pin for all transfers of a write.
+    pub fn with_data_command_held(mut self, hold: bool) -> Self {
+        self.hold_data_command = hold;
+        self
+    }
+
+    /// Write data over SPI.
+    ///
+    /// This method sets the data/command pin and sends a byte array of data to the display over
+    /// the SPI interface. The data is split into transfers of at most the maximum transfer length.
+    ///
+    /// # Arguments
+    ///
+    /// * `is_data` - The level of the data/command pin, `true` for data.
+    /// * `data` - A slice of bytes representing the data to send.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), <Self as DisplayInterface>::Error>` - Returns `Ok(())` on success, or an
+    ///   error if setting the pin or the write operation fails.
+    async fn write(
+        &mut self,
+        is_data: bool,
+        data: &[u8],
+    ) -> Result<(), <Self as DisplayInterface>::Error> {
+        for (index, data_chunk) in data.chunks(self.max_transfer_len).enumerate() {
+            if index == 0 || !self.hold_data_command {
+                self.data_command_pin
+                    .set_state(is_data.into())
+                    .map_err(SSD1677Error::Pin)?;
+            }
+            self.spi
+                .write(data_chunk)
+                .await
+                .map_err(SSD1677Error::Spi)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Implement the async DisplayInterface functions
+impl<SPI, OUT, BUSY, T, R> DisplayInterface for Interface4Pin<SPI, OUT, BUSY, T, R>
+where
+    SPI: embedded_hal_async::spi::SpiDevice,
+    OUT: embedded_hal::digital::OutputPin,
+    BUSY: BusyPin,
+    T: BusyTimer,
+    R: ResetDelay,
+{
+    type Error = SSD1677Error<SPI::Error, OUT::Error, BUSY::Error>;
+
+    async fn reset<D: embedded_hal_async::delay::DelayNs>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<(), Self::Error> {
+        // Disable the display, the wait for the controller to catch up
+        self.reset_pin.set_low().map_err(SSD1677Error::Pin)?;
+        delay.delay_ms(RESET_DELAY_MS.into()).await;
+        // Enable the display, the wait for the controller to catch up
+        self.reset_pin.set_high().map_err(SSD1677Error::Pin)?;
+        delay.delay_ms(RESET_DELAY_MS.into()).await;
+
+        Ok(())
+    }
+
+    async fn send_command(&mut self, command: u8) -> Result<(), Self::Error> {
+        // Send the command with the data/command pin low
+        self.write(false, &[command]).await?;
+
+        // Wait for the device to be ready
+        self.busy_wait().await?;
+
+        Ok(())
+    }
+
+    async fn send_data(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        // Send the data with the data/command pin high
+        self.write(true, data).await?;
+
+        // Wait for the device to be ready
+        self.busy_wait().await?;
+
+        Ok(())
+    }
+
+    async fn send_data_without_busy_wait(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        // Send the data with the data/command pin high
+        self.write(true, data).await?;
+
+        Ok(())
+    }
+
+    async fn read_command(
+        &mut self,
+        command: u8,
+        dummy: &mut [u8],
+        data: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        // Send the command with the data/command pin low
+        self.write(false, &[command]).await?;
+
+        // The controller clocks out the data while the data/command pin is high, see the
+        // blocking Interface4Pin
+        self.data_command_pin
+            .set_high()
+            .map_err(SSD1677Error::Pin)?;
+        self.spi
+            .transaction(&mut [Operation::Read(dummy), Operation::Read(data)])
+            .await
+            .map_err(SSD1677Error::Spi)?;
+
+        Ok(())
+    }
+
+    async fn wake(&mut self) -> Result<(), Self::Error> {
+        // Leave the reset pin alone if the reset can not be timed
+        if !self.reset_delay.can_reset() {
+            return Err(SSD1677Error::NoResetDelay);
+        }
+
+        // Disable the display, then wait for the controller to catch up
+        self.reset_pin.set_low().map_err(SSD1677Error::Pin)?;
+        self.reset_delay.wait_ms(RESET_DELAY_MS.into()).await;
+        // Enable the display, then wait for the controller to catch up
+        self.reset_pin.set_high().map_err(SSD1677Error::Pin)?;
+        self.reset_delay.wait_ms(RESET_DELAY_MS.into()).await;
+
+        Ok(())
+    }
+
+    async fn busy_wait(&mut self) -> Result<(), Self::Error> {
+        let idle = self.busy_pin.wait_until_idle();
+        let timeout = self.busy_timer.timeout();
+
+        match until_timeout(idle, timeout).await {
+            Some(result) => result.map_err(SSD1677Error::Busy),
+            None => Err(SSD1677Error::BusyTimeout),
+        }
+    }
+}
+
+/// Await a future unless the timeout completes first, returns `None` on timeout.
+async fn until_timeout<F: Future>(
+    future: F,
+    timeout: impl Future<Output = ()>,
+) -> Option<F::Output> {
+    let mut future = pin!(future);
+    let mut timeout = pin!(timeout);
+
+    poll_fn(|cx| {
+        // Poll the future first, so it wins when both are ready
+        if let Poll::Ready(output) = future.as_mut().poll(cx) {
+            return Poll::Ready(Some(output));
+        }
+        timeout.as_mut().poll(cx).map(|()| None)
+    })
+    .await
+}
+
+/// Future that returns `Pending` once, handing control back to the executor.
+struct YieldNow {
+    yielded: bool,
+}
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.yielded {
+            Poll::Ready(())
+        } else {
+            // Ask to be polled again as soon as the executor is free
+            self.yielded = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
@@ -6,16 +6,41 @@
 //! and updating its contents. It uses a generic interface that implements the
 //! [DisplayInterface] and [DisplayCommands] traits, allowing for flexibility in
 //! hardware implementations.
-use crate::command;
 use crate::command::*;
-use crate::config::Config;
+use crate::config::{Config, InitStep};
+use crate::error::RegionError;
 use crate::interface::DisplayInterface;
+use crate::lut::Lut;
 
 /// Maximum number of gate outputs for the display
 pub const MAX_GATE_OUTPUTS: u16 = 680;
 /// Maximum number of source outputs for the display
 pub const MAX_SOURCE_OUTPUTS: u16 = 960;
 
+/// Display update sequence that only loads the temperature
+pub(crate) const UPDATE_LOAD_TEMPERATURE: UpdateSequence = UpdateSequence::new()
+    .enable_clock()
+    .load_temperature()
+    .disable_clock();
+
+/// Display update sequence that loads the LUT from OTP without refreshing the display
+pub(crate) const UPDATE_LOAD_LUT: UpdateSequence = UpdateSequence::new()
+    .enable_clock()
+    .load_temperature()
+    .load_lut()
+    .disable_clock();
+
+/// Returns true for the commands of an init sequence that write to RAM
+pub(crate) fn writes_ram(command: &Command<'_>) -> bool {
+    matches!(
+        command,
+        Command::WriteRamBlackAndWhite(_)
+            | Command::WriteRamRed(_)
+            | Command::AutoWriteBlackAndWhiteRamRegularPattern(_)
+            | Command::AutoWriteRedRamRegularPattern(_)
+    )
+}
+
 #[cfg(feature = "defmt")]
 #[derive(defmt::Format)]
 /// The display's dimensions
@@ -52,15 +77,98 @@ pub enum Rotation {
 /// The different enum values take different amount of times, and yield different quality results.
 /// - The [Slow] value ensures the entire display is clear and yields a crisp image
 /// - The [Fast] value ensures a quick update, but there may be some visual ghosting.
+/// - The [TemperatureOverride] value loads the OTP LUT for a given temperature instead of the
+///   measured one. The LUTs for high temperatures drive the panel for a shorter time, so this
+///   gives a full refresh in roughly a second on many panels.
+/// - The [Custom] value runs any [UpdateSequence], for example to leave the analog circuits
+///   powered between quick successive updates.
 ///
+/// All modes but [Custom] load the waveform LUT from OTP. When a custom LUT is loaded with
+/// [BasicDisplay::load_lut] the custom LUT is used instead.
 ///
 /// [Slow]: self::DisplayUpdateMode::Slow
 /// [Fast]: self::DisplayUpdateMode::Fast
+/// [TemperatureOverride]: self::DisplayUpdateMode::TemperatureOverride
+/// [Custom]: self::DisplayUpdateMode::Custom
 pub enum DisplayUpdateMode {
     /// Perform a "fast" update, this can struggle to clear pixels
-    Fast = 0xFF,
+    Fast,
     /// Perform a "slow" update, this takes a while, but the result is clean
-    Slow = 0xF7,
+    Slow,
+    /// Perform a full update with the OTP LUT for the given temperature.
+    ///
+    /// The temperature is written to the temperature register before the update, the right
+    /// value depends on the LUTs in the OTP of the panel. The register is measured again by the
+    /// next [Fast] or [Slow] update.
+    ///
+    /// [Fast]: self::DisplayUpdateMode::Fast
+    /// [Slow]: self::DisplayUpdateMode::Slow
+    TemperatureOverride(Celsius),
+    /// Perform an update with a custom display update sequence
+    Custom(UpdateSequence),
+}
+
+impl DisplayUpdateMode {
+    /// Returns the display update sequence sent with the display update control 2 command
+    pub fn sequence(&self) -> UpdateSequence {
+        // Every update powers up, loads the LUT and powers down again around the display step
+        let full = |sequence: UpdateSequence, mode| {
+            sequence
+                .enable_clock()
+                .enable_analog()
+                .load_lut()
+                .display(mode)
+                .disable_analog()
+                .disable_clock()
+        };
+
+        match self {
+            DisplayUpdateMode::Fast => {
+                full(UpdateSequence::new().load_temperature(), DisplayMode::Mode2)
+            }
+            DisplayUpdateMode::Slow => {
+                full(UpdateSequence::new().load_temperature(), DisplayMode::Mode1)
+            }
+            // Like Slow, but without loading the temperature so the written one is used
+            DisplayUpdateMode::TemperatureOverride(_) => {
+                full(UpdateSequence::new(), DisplayMode::Mode1)
+            }
+            DisplayUpdateMode::Custom(sequence) => *sequence,
+        }
+    }
+
+    /// Returns the temperature to write to the temperature register before the update, if any
+    pub fn temperature(&self) -> Option<Celsius> {
+        match self {
+            DisplayUpdateMode::TemperatureOverride(temperature) => Some(*temperature),
+            _ => None,
+        }
+    }
+}
+
+/// A rectangular region of the display RAM.
+///
+/// The region is given in the native orientation of the controller, where `x` runs along the
+/// columns (source outputs) and `y` runs along the rows (gate outputs), regardless of the
+/// configured [Rotation]. Eight horizontal pixels are packed into every byte, so `x` and `width`
+/// must be multiples of 8.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Region {
+    /// The first column of the region
+    pub x: u16,
+    /// The first row of the region
+    pub y: u16,
+    /// The number of columns in the region
+    pub width: u16,
+    /// The number of rows in the region
+    pub height: u16,
+}
+
+impl Region {
+    /// Returns the number of bytes needed to hold one plane of the region
+    pub fn buffer_len(&self) -> usize {
+        (self.width as usize / 8) * self.height as usize
+    }
 }
 
 impl Default for Rotation {
@@ -71,20 +179,20 @@ impl Default for Rotation {
 }
 
 /// A configured display with a hardware interface
-pub struct BasicDisplay<I, SPI>
+pub struct BasicDisplay<I>
 where
-    SPI: embedded_hal::spi::SpiDevice,
-    I: DisplayInterface + DisplayCommands<SPI>,
+    I: DisplayInterface,
 {
     pub(crate) interface: I,   // The interface for communicating with the display
     pub(crate) config: Config, // The display configuration
-    _phantom: core::marker::PhantomData<SPI>, // Phantom data to hold the SPI type
+    asleep: bool,              // Whether the controller is in deep sleep
+    ram_invalid: bool,         // Whether the RAM may differ from the last full update
+    lut: Option<Lut>,          // The custom waveform LUT, if loaded
 }
 
-impl<I, SPI> BasicDisplay<I, SPI>
+impl<I> BasicDisplay<I>
 where
-    I: DisplayInterface + DisplayCommands<SPI>,
-    SPI: embedded_hal::spi::SpiDevice,
+    I: DisplayInterface,
 {
     /// Create a new display instance from a [DisplayInterface] and a [Config].
     ///
@@ -98,8 +206,9 @@ where
         Self {
             interface,
             config,
-            // TODO: Figure out if I can remove PhantomData
-            _phantom: core::marker::PhantomData,
+            asleep: false,
+            ram_invalid: true,
+            lut: None,
         }
     }
 
@@ -120,85 +229,279 @@ where
         delay: &mut D,
     ) -> Result<(), <I as DisplayInterface>::Error> {
         // Perform the hardware reset
-        self.interface.reset_hardware(delay);
+        self.interface.reset_hardware(delay)?;
 
         // Perform the software reset
-        self.interface
-            .reset_software()
-            .expect("Failed to soft-reset the device");
+        self.interface.reset_software()?;
 
         // Wait for the display to be ready
-        self.interface.busy_wait();
+        self.interface.busy_wait()?;
+
+        // The controller is awake after a hardware reset
+        self.asleep = false;
 
         // Re-initialize the display
         self.init()
     }
 
+    /// Put the controller into deep sleep.
+    ///
+    /// The controller draws very little power in deep sleep, and the panel keeps showing its
+    /// last image. The controller is woken automatically by the next update, which performs a
+    /// hardware reset and restores the registers set by [BasicDisplay::init], without the RAM
+    /// fill and refresh that would clear the panel. The RAM is marked invalid, see
+    /// [BasicDisplay::ram_invalid], so the next update sends the entire display.
+    ///
+    /// The hardware reset is done by [DisplayInterface::wake], so the interface needs a delay to
+    /// time it, see [Interface4Pin::with_reset_delay](crate::interface::Interface4Pin::with_reset_delay).
+    ///
+    /// # Arguments
+    ///
+    /// * `mode` - The deep sleep mode to enter, see [DeepSleepMode]. Sending
+    ///   [DeepSleepMode::Normal] leaves the controller awake.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), <I as DisplayInterface>::Error>` - Returns Ok on success, or an error if sending the command fails.
+    pub fn sleep(&mut self, mode: DeepSleepMode) -> Result<(), <I as DisplayInterface>::Error> {
+        self.interface.deep_sleep(mode)?;
+        self.asleep = mode != DeepSleepMode::Normal;
+        if self.asleep {
+            self.ram_invalid = true;
+        }
+
+        Ok(())
+    }
+
+    /// Returns true if the controller was put into deep sleep and has not been woken since
+    pub fn is_asleep(&self) -> bool {
+        self.asleep
+    }
+
+    /// Wake the controller if it is in deep sleep, restoring its registers.
+    ///
+    /// Unlike [BasicDisplay::init] the RAM is not filled and the display is not refreshed, so
+    /// the panel keeps showing its image. The RAM content may still be lost in deep sleep, so
+    /// callers must rewrite the whole display after waking.
+    fn wake_if_asleep(&mut self) -> Result<(), <I as DisplayInterface>::Error> {
+        if !self.asleep {
+            return Ok(());
+        }
+
+        // Deep sleep can only be left through a hardware reset
+        self.interface.wake()?;
+        self.interface.reset_software()?;
+        self.interface.busy_wait()?;
+        self.asleep = false;
+        self.ram_invalid = true;
+
+        // Restore the registers of the init sequence, leaving the RAM alone and loading the
+        // waveform LUT without refreshing the display
+        for &step in self.config.init_sequence {
+            match step {
+                InitStep::Command(command) if writes_ram(&command) => {}
+                InitStep::LoadLut(_) => self.load_waveform(UPDATE_LOAD_LUT)?,
+                step => self.init_step(step)?,
+            }
+        }
+
+        Ok(())
+    }
+
     /// Initialize the display controller according to the datasheet.
     ///
-    /// This method configures the display settings as specified in the SSD1677 datasheet,
-    /// including filling the RAM, setting the driver output control, and loading the waveform LUT.
+    /// This method sends the init sequence of the [Config], which by default configures the
+    /// display settings as specified in the SSD1677 datasheet, including setting the driver
+    /// output control and the RAM size for the dimensions, and loading the waveform LUT.
     ///
     /// # Returns
     ///
     /// * `Result<(), <I as DisplayInterface>::Error>` - Returns Ok on success, or an error if initialization fails.
     pub fn init(&mut self) -> Result<(), <I as DisplayInterface>::Error> {
         // 3. Send intialization code
-        // Clear and fill RAM
-        self.interface
-            .auto_write_ram_black_and_white_regular_pattern(0xF7)
-            .expect("Failed to fill BW RAM");
-        self.interface
-            .auto_write_ram_red_regular_pattern(0xF7)
-            .expect("Failed to fill RED RAM");
+        // 4. Load waveform LUT
+        // Both are part of the init sequence of the panel
+        for &step in self.config.init_sequence {
+            self.init_step(step)?;
+        }
+        self.ram_invalid = true;
 
-        // Set gate driver output
-        self.interface
-            .set_driver_output_control_from_width(self.config.dimensions.rows)
-            .expect("Failed to set gate control");
+        // Wait for the display to be ready
+        self.interface.busy_wait()
+    }
 
-        // Set the data entry mode
-        self.interface
-            .set_data_entry_mode(
-                DataEntryMode::IncrementXIncrementY,
-                IncrementAxis::Horizontal,
-            )
-            .expect("Failed to set data entry mode");
+    /// Send a step of the init sequence, filling in the dimensions of the display
+    fn init_step(&mut self, step: InitStep) -> Result<(), <I as DisplayInterface>::Error> {
+        let Dimensions { rows, cols } = self.config.dimensions;
+        match step {
+            InitStep::Command(command) => self.interface.execute(command),
+            InitStep::DriverOutputControl(scanning) => {
+                self.interface.set_driver_output_control(rows - 1, scanning)
+            }
+            InitStep::RamSize => self.interface.set_ram_address_based_on_size(rows, cols),
+            InitStep::LoadLut(sequence) => self.load_waveform(sequence),
+        }
+    }
 
-        // Set the display RAM size
-        self.interface
-            .set_ram_address_based_on_size(self.config.dimensions.rows, self.config.dimensions.cols)
-            .expect("Failed to set RAM address");
+    /// Load the waveform LUT.
+    ///
+    /// A custom LUT is written to the controller, otherwise the LUT is loaded from OTP by running
+    /// the display update sequence.
+    fn load_waveform(
+        &mut self,
+        sequence: UpdateSequence,
+    ) -> Result<(), <I as DisplayInterface>::Error> {
+        match self.lut {
+            // Write the custom waveform LUT
+            Some(lut) => self.interface.write_lut(&lut)?,
+            None => {
+                // Set waveform LUT from OTP
+                self.interface.update_display_option2(sequence)?;
+                // Run the update sequence
+                self.interface.refresh_display()?;
+            }
+        }
 
-        // Set the panel border waveform control
-        self.interface
-            .set_border_waveform_control(
-                command::WaveformVDBOption::Transition,
-                command::VDBFixedLevelSetting::VSS,
-                command::VDBGSTransitionSetting::LUT1,
-            )
-            .expect("Failed to set waveform control");
+        // Wait for the display to be ready
+        self.interface.busy_wait()
+    }
 
-        // 4. Load waveform LUT
-        // Set temperature sensor
-        self.interface
-            .set_temperature_sensor(command::TemperatureSensor::Internal)
-            .expect("Failed to set temp sensor");
-        // Set waveform LUT from OTP
-        self.interface
-            .update_display_option2(0xFF)
-            .expect("Failed to load waveform LUT");
-        // Force display refresh
-        self.interface
-            .refresh_display()
-            .expect("Failed to refresh self.interfacelay");
+    /// Load a custom waveform LUT in place of the LUT from OTP.
+    ///
+    /// The LUT is written to the controller straight away, and again whenever the display is
+    /// initialized, as the controller loses it on a reset or deep sleep. While a custom LUT is
+    /// loaded, updates no longer load the LUT from OTP, see [DisplayUpdateMode].
+    ///
+    /// # Arguments
+    ///
+    /// * `lut` - The waveform LUT to use, see [Lut].
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), <I as DisplayInterface>::Error>` - Returns Ok on success, or an error if writing the LUT fails.
+    pub fn load_lut(&mut self, lut: Lut) -> Result<(), <I as DisplayInterface>::Error> {
+        self.lut = Some(lut);
 
-        // Wait for the display to be ready
-        self.interface.busy_wait();
+        // A sleeping controller gets the LUT when it is woken
+        if !self.asleep {
+            self.interface.write_lut(&lut)?;
+        }
 
         Ok(())
     }
 
+    /// Go back to using the waveform LUT from OTP.
+    ///
+    /// The LUT from OTP is loaded again by the next update.
+    pub fn use_otp_lut(&mut self) {
+        self.lut = None;
+    }
+
+    /// Returns the custom waveform LUT, if one is loaded
+    pub fn lut(&self) -> Option<&Lut> {
+        self.lut.as_ref()
+    }
+
+    /// Send the display update sequence for the update mode.
+    ///
+    /// Loading the LUT from OTP would replace a custom LUT, so it is left out while one is
+    /// loaded, and the temperature of the update mode is not needed.
+    fn set_update_mode(
+        &mut self,
+        update_mode: DisplayUpdateMode,
+    ) -> Result<(), <I as DisplayInterface>::Error> {
+        let sequence = match self.lut {
+            Some(_) => update_mode.sequence().without_loading(),
+            None => {
+                if let Some(temperature) = update_mode.temperature() {
+                    self.interface.write_temperature(temperature)?;
+                }
+                update_mode.sequence()
+            }
+        };
+
+        self.interface.update_display_option2(sequence)
+    }
+
+    /// Measure the temperature with the selected temperature sensor.
+    ///
+    /// This runs a display update sequence that only loads the temperature, without refreshing
+    /// the display, then reads the temperature register. The controller is woken first if it is
+    /// in deep sleep.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Celsius, <I as DisplayInterface>::Error>` - Returns the temperature on success, or an error if reading it fails.
+    pub fn temperature(&mut self) -> Result<Celsius, <I as DisplayInterface>::Error> {
+        self.wake_if_asleep()?;
+
+        // Load the temperature into the register
+        self.interface
+            .update_display_option2(UPDATE_LOAD_TEMPERATURE)?;
+        self.interface.refresh_display()?;
+
+        self.interface.read_temperature()
+    }
+
+    /// Read the contents of a RAM plane of the controller into a buffer.
+    ///
+    /// The RAM is read from the start, the buffer should be `rows * cols / 8` bytes long to
+    /// read the entire display. This can be used to verify the data written by an update.
+    /// The controller is woken first if it is in deep sleep.
+    ///
+    /// # Arguments
+    ///
+    /// * `plane` - The RAM plane to read, see [RamPlane].
+    /// * `buffer` - The buffer to read the RAM into.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), <I as DisplayInterface>::Error>` - Returns Ok on success, or an error if reading the RAM fails.
+    pub fn read_ram(
+        &mut self,
+        plane: RamPlane,
+        buffer: &mut [u8],
+    ) -> Result<(), <I as DisplayInterface>::Error> {
+        self.wake_if_asleep()?;
+
+        // Reset the address
+        self.interface.set_ram_x_count(0)?;
+        self.interface.set_ram_y_count(0)?;
+
+        self.interface.read_ram(plane, buffer)
+    }
+
+    /// Read the status of the controller.
+    ///
+    /// A controller that is missing or not responding reads back the level of the idle data
+    /// line, so the status can be used to check for the controller at boot.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Status, <I as DisplayInterface>::Error>` - Returns the status on success, or an error if reading it fails.
+    pub fn status(&mut self) -> Result<Status, <I as DisplayInterface>::Error> {
+        self.interface.read_status()
+    }
+
+    /// Returns a mutable reference to the interface, to send commands not covered by the display.
+    ///
+    /// Commands that change the RAM window, data entry mode or waveform can break later updates.
+    /// The RAM is treated as changed, see [BasicDisplay::ram_invalid].
+    pub fn interface_mut(&mut self) -> &mut I {
+        self.ram_invalid = true;
+        &mut self.interface
+    }
+
+    /// Returns true if the RAM may no longer hold the black and white buffer of the last
+    /// [BasicDisplay::update].
+    ///
+    /// This is the case before the first update, after [BasicDisplay::init], after waking from
+    /// deep sleep, and after writing other data with [BasicDisplay::update_region] or through
+    /// [BasicDisplay::interface_mut].
+    pub fn ram_invalid(&self) -> bool {
+        self.ram_invalid
+    }
+
     /// Update the display contents by writing the supplied buffers to the controller.
     ///
     /// This function takes two optional buffers: one for the black and white pixels
@@ -210,9 +513,9 @@ where
     /// # Arguments
     ///
     /// * `bw_buffer` - an optional slice of bytes representing the black and white pixel data.
-    ///                 If `None`, the black and white RAM will not be updated.
+    ///   If `None`, the black and white RAM will not be updated.
     /// * `red_buffer` - An optional slice of bytes representing the red pixel data.
-    ///                  If `None`, the red RAM will not be updated.
+    ///   If `None`, the red RAM will not be updated.
     /// * `update_mode` - The kond of update to do, see [DisplayUpdateMode]
     ///
     /// # Returns
@@ -225,47 +528,187 @@ where
         red_buffer: Option<&[u8]>,
         update_mode: DisplayUpdateMode,
     ) -> Result<(), <I as DisplayInterface>::Error> {
+        self.wake_if_asleep()?;
+
         // Write the black and white RAM if provided
         if let Some(buffer) = bw_buffer {
             // Reset the address
-            self.interface
-                .set_ram_x_count(0)
-                .expect("Failed to set RAM address for x");
-            self.interface
-                .set_ram_y_count(0)
-                .expect("Failed to set RAM address for y");
+            self.interface.set_ram_x_count(0)?;
+            self.interface.set_ram_y_count(0)?;
 
             // Copy the data
-            self.interface
-                .write_ram_black_and_white(buffer)
-                .expect("Failed to write black and white RAM buffer");
+            self.interface.write_ram_black_and_white(buffer)?;
+            self.ram_invalid = false;
         }
 
         // Write the red RAM if provided
         if let Some(buffer) = red_buffer {
             // Reset the address
-            self.interface
-                .set_ram_x_count(0)
-                .expect("Failed to set RAM address for x");
-            self.interface
-                .set_ram_y_count(0)
-                .expect("Failed to set RAM address for y");
+            self.interface.set_ram_x_count(0)?;
+            self.interface.set_ram_y_count(0)?;
 
             // Copy the data
-            self.interface
-                .write_ram_red(buffer)
-                .expect("Failed to write RED RAM buffer");
+            self.interface.write_ram_red(buffer)?;
         }
 
         // Set the update mode
-        self.interface
-            .update_display_option2(update_mode as u8)
-            .unwrap();
+        self.set_update_mode(update_mode)?;
 
         // Refresh the display
+        self.interface.refresh_display()?;
+
+        Ok(())
+    }
+
+    /// Update a region of the display by writing the supplied buffers to the controller.
+    ///
+    /// Only the bytes of the region are sent, the RAM window of the controller is restricted to
+    /// the region while writing, and restored to the full display afterwards. The rest of the
+    /// display RAM keeps its previous contents.
+    ///
+    /// # Arguments
+    ///
+    /// * `region` - The region of the display RAM to write, see [Region].
+    /// * `bw_buffer` - An optional slice of bytes with the black and white pixel data of the
+    ///   region, row by row. If `None`, the black and white RAM will not be updated.
+    /// * `red_buffer` - An optional slice of bytes with the red pixel data of the region, row by
+    ///   row. If `None`, the red RAM will not be updated.
+    /// * `update_mode` - The kind of update to do, see [DisplayUpdateMode]
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), RegionError<<I as DisplayInterface>::Error>>` - Returns `Ok(())` on success.
+    ///   Returns [RegionError::InvalidRegion] if the region is empty, does not fit within the
+    ///   display, or `x` or `width` is not a multiple of 8, and [RegionError::InvalidBufferLength]
+    ///   if a buffer is not exactly [Region::buffer_len] bytes long. Nothing is sent to the
+    ///   controller in these cases. Otherwise an error is returned if writing to the RAM or
+    ///   refreshing the display fails.
+    // `is_multiple_of` needs Rust 1.87, keep `%` so older compilers are supported
+    #[allow(clippy::manual_is_multiple_of)]
+    pub fn update_region(
+        &mut self,
+        region: Region,
+        bw_buffer: Option<&[u8]>,
+        red_buffer: Option<&[u8]>,
+        update_mode: DisplayUpdateMode,
+    ) -> Result<(), RegionError<<I as DisplayInterface>::Error>> {
+        // Validate the region, without overflowing at the end of the u16 range
+        let fits = match (
+            region.x.checked_add(region.width),
+            region.y.checked_add(region.height),
+        ) {
+            (Some(x_end), Some(y_end)) => x_end <= self.cols() && y_end <= self.rows(),
+            _ => false,
+        };
+        if !fits
+            || region.width == 0
+            || region.height == 0
+            || region.x % 8 != 0
+            || region.width % 8 != 0
+        {
+            return Err(RegionError::InvalidRegion);
+        }
+
+        // Validate the buffers
+        for buffer in [bw_buffer, red_buffer].into_iter().flatten() {
+            if buffer.len() != region.buffer_len() {
+                return Err(RegionError::InvalidBufferLength);
+            }
+        }
+
+        let row_len = region.width as usize / 8;
+        self.write_region(region, bw_buffer, red_buffer, row_len, update_mode)?;
+
+        // The region holds data that is not part of the last full update
+        if bw_buffer.is_some() {
+            self.ram_invalid = true;
+        }
+
+        Ok(())
+    }
+
+    /// Write pixel data to a region of the display RAM, then refresh the display.
+    ///
+    /// Every buffer must start with the first byte of the region, and hold the following rows
+    /// every `stride` bytes. The region and buffers are expected to have been validated by the
+    /// caller.
+    pub(crate) fn write_region(
+        &mut self,
+        region: Region,
+        bw_buffer: Option<&[u8]>,
+        red_buffer: Option<&[u8]>,
+        stride: usize,
+        update_mode: DisplayUpdateMode,
+    ) -> Result<(), <I as DisplayInterface>::Error> {
+        self.wake_if_asleep()?;
+        let row_len = region.width as usize / 8;
+
+        // Restrict the RAM window to the region, the address wraps to the next row at its end
+        self.interface
+            .set_ram_x_address(region.x, region.x + region.width - 1)?;
         self.interface
-            .refresh_display()
-            .expect("Failed to refresh the display");
+            .set_ram_y_address(region.y, region.y + region.height - 1)?;
+
+        // Write the black and white RAM if provided
+        if let Some(buffer) = bw_buffer {
+            // Move the address to the start of the region
+            self.interface.set_ram_x_count(region.x)?;
+            self.interface.set_ram_y_count(region.y)?;
+
+            // Copy the data
+            self.write_ram_rows(RamPlane::BlackAndWhite, buffer, row_len, stride)?;
+        }
+
+        // Write the red RAM if provided
+        if let Some(buffer) = red_buffer {
+            // Move the address to the start of the region
+            self.interface.set_ram_x_count(region.x)?;
+            self.interface.set_ram_y_count(region.y)?;
+
+            // Copy the data
+            self.write_ram_rows(RamPlane::Red, buffer, row_len, stride)?;
+        }
+
+        // Restore the RAM window so full updates cover the entire display again
+        self.interface.set_ram_address_based_on_size(
+            self.config.dimensions.rows,
+            self.config.dimensions.cols,
+        )?;
+
+        // Set the update mode
+        self.set_update_mode(update_mode)?;
+
+        // Refresh the display
+        self.interface.refresh_display()?;
+
+        Ok(())
+    }
+
+    /// Write the rows of a region to a RAM plane with a single RAM write command.
+    ///
+    /// `row_len` bytes are sent from every `stride` bytes of `buffer`. The address counters
+    /// carry on from one data byte to the next, so the rows follow each other in the RAM window.
+    fn write_ram_rows(
+        &mut self,
+        plane: RamPlane,
+        buffer: &[u8],
+        row_len: usize,
+        stride: usize,
+    ) -> Result<(), <I as DisplayInterface>::Error> {
+        let write_ram = match plane {
+            RamPlane::BlackAndWhite => Command::WriteRamBlackAndWhite,
+            RamPlane::Red => Command::WriteRamRed,
+        };
+
+        // The rows are contiguous, send them all at once
+        if row_len == stride {
+            return self.interface.execute(write_ram(buffer));
+        }
+
+        self.interface.send_command(write_ram(&[]).opcode())?;
+        for row in buffer.chunks(stride) {
+            self.interface.send_data(&row[..row_len])?;
+        }
 
         Ok(())
     }
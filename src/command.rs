@@ -1,10 +1,12 @@
 //! This module defines the commands to the [BasicDisplay](crate::basic_display::BasicDisplay) and the valid options to those commands.
-use crate::interface::{DisplayInterface, Interface4Pin};
+use crate::interface::DisplayInterface;
+use crate::lut::{Lut, LUT_LEN};
+use crate::voltage::{GateVoltage, SourceVoltage, VcomVoltage};
 
 /// The address increment orientation when writing image data.
 /// This configures how the controller auto-increments the row and column address when data is
 /// written using the WriteImageData command.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[repr(u8)]
 pub enum IncrementAxis {
     /// X direction
@@ -13,7 +15,18 @@ pub enum IncrementAxis {
     Vertical = 0b1,
 }
 
-#[derive(Clone, Copy)]
+impl IncrementAxis {
+    /// Returns the increment axis for the AM bit of the data entry mode setting
+    pub(crate) fn from_bits(bits: u8) -> Option<Self> {
+        match bits {
+            0b0 => Some(IncrementAxis::Horizontal),
+            0b1 => Some(IncrementAxis::Vertical),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[repr(u8)]
 pub enum DataEntryMode {
     DecrementXDecrementY = 0b00,
@@ -22,40 +35,340 @@ pub enum DataEntryMode {
     IncrementXIncrementY = 0b11,
 }
 
-#[derive(Clone, Copy)]
+impl DataEntryMode {
+    /// Returns the data entry mode for the ID bits of the data entry mode setting
+    pub(crate) fn from_bits(bits: u8) -> Option<Self> {
+        match bits {
+            0b00 => Some(DataEntryMode::DecrementXDecrementY),
+            0b01 => Some(DataEntryMode::IncrementXDecrementY),
+            0b10 => Some(DataEntryMode::DecrementXIncrementY),
+            0b11 => Some(DataEntryMode::IncrementXIncrementY),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[repr(u8)]
 pub enum TemperatureSensor {
     Internal = 0x80,
     External = 48,
 }
 
+impl TemperatureSensor {
+    /// Returns the temperature sensor for the data of the temperature sensor selection
+    pub(crate) fn from_bits(bits: u8) -> Option<Self> {
+        match bits {
+            0x80 => Some(TemperatureSensor::Internal),
+            48 => Some(TemperatureSensor::External),
+            _ => None,
+        }
+    }
+}
+
+/// A temperature in degrees Celsius, as held by the temperature register of the controller.
+///
+/// The register is a 12-bit two's complement value in steps of 1/16 °C.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Celsius(i16);
+
+impl Celsius {
+    /// Create a temperature from whole degrees Celsius
+    pub const fn from_degrees(degrees: i8) -> Self {
+        Celsius(degrees as i16 * 16)
+    }
+
+    /// Create a temperature from a number of 1/16 °C steps.
+    ///
+    /// Values outside of the 12-bit range of the register are clamped to it.
+    pub fn from_sixteenths(sixteenths: i16) -> Self {
+        Celsius(sixteenths.clamp(-2048, 2047))
+    }
+
+    /// Returns the temperature in 1/16 °C steps
+    pub fn sixteenths(&self) -> i16 {
+        self.0
+    }
+
+    /// Returns the temperature in whole degrees Celsius, rounded down
+    pub fn degrees(&self) -> i16 {
+        self.0 >> 4
+    }
+
+    /// Returns the temperature in degrees Celsius
+    pub fn as_f32(&self) -> f32 {
+        self.0 as f32 / 16.0
+    }
+
+    /// Create a temperature from the two bytes of the temperature register
+    pub(crate) fn from_register(bytes: [u8; 2]) -> Self {
+        // The 12 bits are left aligned, shift back to sign extend them
+        Celsius(i16::from_be_bytes(bytes) >> 4)
+    }
+
+    /// Returns the two bytes of the temperature register for the temperature
+    pub(crate) fn to_register(self) -> [u8; 2] {
+        (self.0 << 4).to_be_bytes()
+    }
+}
+
 /// Ram display update option, see page 27 in the datasheet
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum RamOption {
     Normal = 0b0000,
     Bypass = 0b0100,
     Invert = 0b1000,
 }
 
-#[derive(Clone, Copy)]
+impl RamOption {
+    /// Returns the RAM option for a nibble of the display update control 1 data
+    pub(crate) fn from_bits(bits: u8) -> Option<Self> {
+        match bits {
+            0b0000 => Some(RamOption::Normal),
+            0b0100 => Some(RamOption::Bypass),
+            0b1000 => Some(RamOption::Invert),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
 pub enum DeepSleepMode {
     /// Not sleeping
-    Normal,
+    Normal = 0b00,
     /// Deep sleep with RAM preserved
-    PreserveRAM,
+    PreserveRAM = 0b01,
     /// Deep sleep with RAM discarded
-    DiscardRAM,
+    DiscardRAM = 0b11,
+}
+
+impl DeepSleepMode {
+    /// Returns the deep sleep mode for the data of the deep sleep command
+    pub(crate) fn from_bits(bits: u8) -> Option<Self> {
+        match bits {
+            0b00 => Some(DeepSleepMode::Normal),
+            0b01 => Some(DeepSleepMode::PreserveRAM),
+            0b11 => Some(DeepSleepMode::DiscardRAM),
+            _ => None,
+        }
+    }
+}
+
+/// The display mode used by the display step of an [UpdateSequence]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DisplayMode {
+    /// Display mode 1, the full waveform
+    Mode1,
+    /// Display mode 2, usually a shorter waveform for partial updates
+    Mode2,
+}
+
+/// Builder for the display update sequence run by the controller on activation, see page 31 in
+/// the datasheet.
+///
+/// The sequence is sent with [DisplayCommands::update_display_option2] and run by
+/// [DisplayCommands::refresh_display]. The steps run in the order of the builder methods below,
+/// regardless of the order they are added in.
+///
+/// ```
+/// use ssd1677::command::{DisplayMode, UpdateSequence};
+///
+/// // Refresh without powering down the analog circuits, so the next update starts quicker
+/// let sequence = UpdateSequence::new()
+///     .enable_clock()
+///     .enable_analog()
+///     .display(DisplayMode::Mode2);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct UpdateSequence(u8);
+
+impl UpdateSequence {
+    const ENABLE_CLOCK: u8 = 0x80;
+    const ENABLE_ANALOG: u8 = 0x40;
+    const LOAD_TEMPERATURE: u8 = 0x20;
+    const LOAD_LUT: u8 = 0x10;
+    const DISPLAY_MODE_2: u8 = 0x08;
+    const DISPLAY: u8 = 0x04;
+    const DISABLE_ANALOG: u8 = 0x02;
+    const DISABLE_CLOCK: u8 = 0x01;
+
+    /// Create an empty sequence that does nothing
+    pub const fn new() -> Self {
+        UpdateSequence(0)
+    }
+
+    /// Create a sequence from the raw option byte of the command
+    pub const fn from_bits(bits: u8) -> Self {
+        UpdateSequence(bits)
+    }
+
+    /// Returns the raw option byte of the command
+    pub const fn bits(&self) -> u8 {
+        self.0
+    }
+
+    /// Enable the clock signal
+    pub const fn enable_clock(self) -> Self {
+        UpdateSequence(self.0 | Self::ENABLE_CLOCK)
+    }
+
+    /// Enable the analog circuits, this requires the clock
+    pub const fn enable_analog(self) -> Self {
+        UpdateSequence(self.0 | Self::ENABLE_ANALOG)
+    }
+
+    /// Load the temperature from the selected temperature sensor
+    pub const fn load_temperature(self) -> Self {
+        UpdateSequence(self.0 | Self::LOAD_TEMPERATURE)
+    }
+
+    /// Load the waveform LUT for the current temperature from OTP
+    pub const fn load_lut(self) -> Self {
+        UpdateSequence(self.0 | Self::LOAD_LUT)
+    }
+
+    /// Drive the display with the RAM contents, using the given display mode
+    pub const fn display(self, mode: DisplayMode) -> Self {
+        match mode {
+            DisplayMode::Mode1 => UpdateSequence(self.0 | Self::DISPLAY),
+            DisplayMode::Mode2 => UpdateSequence(self.0 | Self::DISPLAY | Self::DISPLAY_MODE_2),
+        }
+    }
+
+    /// Disable the analog circuits after the sequence
+    pub const fn disable_analog(self) -> Self {
+        UpdateSequence(self.0 | Self::DISABLE_ANALOG)
+    }
+
+    /// Disable the clock signal and oscillator after the sequence
+    pub const fn disable_clock(self) -> Self {
+        UpdateSequence(self.0 | Self::DISABLE_CLOCK)
+    }
+
+    /// Remove the steps loading the temperature and the waveform LUT from OTP.
+    ///
+    /// This keeps a LUT written with [DisplayCommands::write_lut] in place.
+    pub const fn without_loading(self) -> Self {
+        UpdateSequence(self.0 & !(Self::LOAD_TEMPERATURE | Self::LOAD_LUT))
+    }
+}
+
+/// The RAM plane to read with [DisplayCommands::read_ram]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum RamPlane {
+    /// The black and white RAM
+    BlackAndWhite = 0x00,
+    /// The red RAM
+    Red = 0x01,
+}
+
+impl RamPlane {
+    /// Returns the RAM plane for the data of the read RAM option
+    pub(crate) fn from_bits(bits: u8) -> Option<Self> {
+        match bits {
+            0x00 => Some(RamPlane::BlackAndWhite),
+            0x01 => Some(RamPlane::Red),
+            _ => None,
+        }
+    }
+}
+
+/// The status of the controller, read with [DisplayCommands::read_status].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Status {
+    /// The high voltages are not ready, set by a high voltage ready detection
+    pub hv_not_ready: bool,
+    /// VCI is below the detection level, set by a VCI detection
+    pub vci_low: bool,
+    /// The controller is busy
+    pub busy: bool,
+    /// The two bit chip ID
+    pub chip_id: u8,
+}
+
+impl Status {
+    /// Create the status from the byte returned by the controller
+    pub(crate) fn from_byte(byte: u8) -> Self {
+        Status {
+            hv_not_ready: byte & 0x20 != 0,
+            vci_low: byte & 0x10 != 0,
+            busy: byte & 0x04 != 0,
+            chip_id: byte & 0x03,
+        }
+    }
+}
+
+/// The number of bytes in the user ID, read with [DisplayCommands::read_user_id]
+pub const USER_ID_LEN: usize = 10;
+
+/// The display options programmed into the OTP of the controller, read with
+/// [DisplayCommands::read_otp_display_option].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct OtpDisplayOption {
+    /// The VCOM OTP selection
+    pub vcom_otp_selection: u8,
+    /// The raw VCOM register value stored in OTP
+    pub vcom: u8,
+    /// The display mode of every waveform setting
+    pub display_mode: [u8; 5],
+    /// The waveform version of the LUTs in OTP
+    pub waveform_version: [u8; 4],
 }
 
-#[derive(Clone, Copy)]
+impl OtpDisplayOption {
+    /// The number of bytes returned by the controller
+    pub(crate) const LEN: usize = 11;
+
+    /// Create the display options from the bytes returned by the controller
+    pub(crate) fn from_bytes(bytes: &[u8; Self::LEN]) -> Self {
+        let mut display_mode = [0u8; 5];
+        display_mode.copy_from_slice(&bytes[2..7]);
+        let mut waveform_version = [0u8; 4];
+        waveform_version.copy_from_slice(&bytes[7..11]);
+
+        OtpDisplayOption {
+            vcom_otp_selection: bytes[0],
+            vcom: bytes[1],
+            display_mode,
+            waveform_version,
+        }
+    }
+
+    /// Returns the VCOM voltage stored in OTP, or `None` if the OTP value is not a valid VCOM
+    /// voltage, for example because it was never programmed
+    pub fn vcom_voltage(&self) -> Option<VcomVoltage> {
+        VcomVoltage::from_code(self.vcom)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[repr(u8)]
 pub enum BoosterInrush {
     Level1 = 0x40,
     Level2 = 0x80,
 }
 
+impl BoosterInrush {
+    /// Returns the inrush level for the last byte of the booster soft start control data
+    pub(crate) fn from_bits(bits: u8) -> Option<Self> {
+        match bits {
+            0x40 => Some(BoosterInrush::Level1),
+            0x80 => Some(BoosterInrush::Level2),
+            _ => None,
+        }
+    }
+}
+
 /// Select VBD option
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[repr(u8)]
 pub enum WaveformVDBOption {
     /// Use the GS transision defined by `VDBGSTransitionSetting`
@@ -67,8 +380,21 @@ pub enum WaveformVDBOption {
     HiZ = 0b11,
 }
 
+impl WaveformVDBOption {
+    /// Returns the VBD option for bits 7-6 of the border waveform control data
+    pub(crate) fn from_bits(bits: u8) -> Option<Self> {
+        match bits {
+            0b00 => Some(WaveformVDBOption::Transition),
+            0b01 => Some(WaveformVDBOption::Fixed),
+            0b10 => Some(WaveformVDBOption::VCOM),
+            0b11 => Some(WaveformVDBOption::HiZ),
+            _ => None,
+        }
+    }
+}
+
 /// Fix Level Setting for VBD
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[repr(u8)]
 pub enum VDBFixedLevelSetting {
     /// POR
@@ -78,8 +404,21 @@ pub enum VDBFixedLevelSetting {
     VSH2 = 0b11,
 }
 
+impl VDBFixedLevelSetting {
+    /// Returns the fixed level for bits 5-4 of the border waveform control data
+    pub(crate) fn from_bits(bits: u8) -> Option<Self> {
+        match bits {
+            0b00 => Some(VDBFixedLevelSetting::VSS),
+            0b01 => Some(VDBFixedLevelSetting::VSH1),
+            0b10 => Some(VDBFixedLevelSetting::VSL),
+            0b11 => Some(VDBFixedLevelSetting::VSH2),
+            _ => None,
+        }
+    }
+}
+
 /// GS Transition setting for VBD
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[repr(u8)]
 pub enum VDBGSTransitionSetting {
     /// POR value
@@ -89,112 +428,287 @@ pub enum VDBGSTransitionSetting {
     LUT3 = 0b11,
 }
 
-/// The commands implemented on the display
-pub trait DisplayCommands<SPI>
-where
-    SPI: embedded_hal::spi::SpiDevice,
-{
-    fn set_driver_output_control(
-        &mut self,
-        max_gate_lines: u16,
-        scanning_sequence_and_direction: u8,
-    ) -> Result<(), SPI::Error>;
-
-    fn set_driver_output_control_from_width(&mut self, width: u16) -> Result<(), SPI::Error>;
-
-    fn set_data_entry_mode(
-        &mut self,
-        data_entry_mode: DataEntryMode,
-        increment_axis: IncrementAxis,
-    ) -> Result<(), SPI::Error>;
-
-    fn write_ram_black_and_white(&mut self, data: &[u8]) -> Result<(), SPI::Error>;
-
-    fn write_ram_red(&mut self, data: &[u8]) -> Result<(), SPI::Error>;
-
-    fn auto_write_ram_red_regular_pattern(&mut self, value: u8) -> Result<(), SPI::Error>;
-
-    fn auto_write_ram_black_and_white_regular_pattern(
-        &mut self,
-        value: u8,
-    ) -> Result<(), SPI::Error>;
-
-    fn set_ram_x_count(&mut self, offset: u16) -> Result<(), SPI::Error>;
-
-    fn set_ram_y_count(&mut self, offset: u16) -> Result<(), SPI::Error>;
-
-    fn refresh_display(&mut self) -> Result<(), SPI::Error>;
-
-    fn set_ram_x_address(&mut self, start: u16, end: u16) -> Result<(), SPI::Error>;
-
-    fn set_ram_y_address(&mut self, start: u16, end: u16) -> Result<(), SPI::Error>;
-
-    fn set_ram_address_based_on_size(&mut self, width: u16, height: u16) -> Result<(), SPI::Error>;
-
-    fn nop(&mut self) -> Result<(), SPI::Error>;
+impl VDBGSTransitionSetting {
+    /// Returns the GS transition for bits 1-0 of the border waveform control data
+    pub(crate) fn from_bits(bits: u8) -> Option<Self> {
+        match bits {
+            0b00 => Some(VDBGSTransitionSetting::LUT0),
+            0b01 => Some(VDBGSTransitionSetting::LUT1),
+            0b10 => Some(VDBGSTransitionSetting::LUT2),
+            0b11 => Some(VDBGSTransitionSetting::LUT3),
+            _ => None,
+        }
+    }
+}
 
-    fn set_gate_driving_voltage(&mut self, voltage: f32) -> Result<(), SPI::Error>;
+/// The largest number of parameter bytes of a [Command], except for the RAM writes which send
+/// their data as is
+pub const MAX_PARAMETERS_LEN: usize = LUT_LEN;
+
+/// A command to the controller with its parameters.
+///
+/// Commands can be stored as data, for example an init sequence in a static table, and sent
+/// with [DisplayInterface::execute]. They are encoded to the bytes sent to the controller with
+/// [Command::encode], and decoded from them with [Command::decode].
+///
+/// Commands that read from the controller only send the command, use the methods of
+/// [DisplayCommands] to read the result.
+///
+/// ```
+/// use ssd1677::command::{
+///     Command, TemperatureSensor, VDBFixedLevelSetting, VDBGSTransitionSetting,
+///     WaveformVDBOption,
+/// };
+///
+/// static INIT: &[Command] = &[
+///     Command::SoftwareReset,
+///     Command::BorderWaveformControl(
+///         WaveformVDBOption::Transition,
+///         VDBFixedLevelSetting::VSS,
+///         VDBGSTransitionSetting::LUT1,
+///     ),
+///     Command::TemperatureSensor(TemperatureSensor::Internal),
+/// ];
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Command<'a> {
+    /// Set the highest gate line, and the scanning sequence and direction
+    DriverOutputControl(u16, u8),
+    /// Set the gate driving voltage
+    GateDrivingVoltage(GateVoltage),
+    /// Set the source driving voltages
+    SourceDrivingVoltage(SourceVoltage),
+    /// Control the inrush current of the booster
+    BoosterSoftStartControl(BoosterInrush),
+    /// Set the deep sleep mode
+    DeepSleepMode(DeepSleepMode),
+    /// Set the data entry mode and the increment axis
+    DataEntryMode(DataEntryMode, IncrementAxis),
+    /// Reset all parameters except deep sleep mode to their default values.
+    /// BUSY will be high while this is in progress.
+    SoftwareReset,
+    /// Select the temperature sensor
+    TemperatureSensor(TemperatureSensor),
+    /// Write the temperature register
+    WriteTemperature(Celsius),
+    /// Read the temperature register
+    ReadTemperature,
+    /// Activate the display update sequence.
+    /// BUSY will be high while this is in progress.
+    UpdateDisplay,
+    /// Set the RAM options of the black and white RAM and the red RAM for the display update
+    UpdateDisplayOption1(RamOption, RamOption),
+    /// Set the display update sequence
+    UpdateDisplayOption2(UpdateSequence),
+    /// Write data to the black and white RAM
+    WriteRamBlackAndWhite(&'a [u8]),
+    /// Write data to the red RAM
+    WriteRamRed(&'a [u8]),
+    /// Read the RAM selected with [Command::ReadRamOption]
+    ReadRam,
+    /// Write the VCOM register
+    WriteVcom(VcomVoltage),
+    /// Read the display options programmed into OTP
+    ReadOtpDisplayOption,
+    /// Read the user ID programmed into OTP
+    ReadUserId,
+    /// Read the status bits
+    ReadStatus,
+    /// Write the waveform LUT, as returned by [Lut::to_bytes]
+    WriteLut(&'a [u8; LUT_LEN]),
+    /// Select the border waveform
+    BorderWaveformControl(
+        WaveformVDBOption,
+        VDBFixedLevelSetting,
+        VDBGSTransitionSetting,
+    ),
+    /// Select the RAM to read with [Command::ReadRam]
+    ReadRamOption(RamPlane),
+    /// Set the start and end of the RAM window in the X direction
+    RamXAddress(u16, u16),
+    /// Set the start and end of the RAM window in the Y direction
+    RamYAddress(u16, u16),
+    /// Fill the red RAM with a single value
+    AutoWriteRedRamRegularPattern(u8),
+    /// Fill the black and white RAM with a single value
+    AutoWriteBlackAndWhiteRamRegularPattern(u8),
+    /// Set the X address counter
+    RamXCount(u16),
+    /// Set the Y address counter
+    RamYCount(u16),
+    /// No operation, can be used to terminate RAM writes and reads
+    Nop,
+}
 
-    fn set_source_driving_voltage(
-        &mut self,
-        vsh1_voltage: f32,
-        vsh2_voltage: f32,
-        vsl_voltage: f32,
-    ) -> Result<(), SPI::Error>;
+impl<'a> Command<'a> {
+    /// Encode the command to its opcode and the parameter bytes sent after it.
+    ///
+    /// The parameters are written to `buf`, except for RAM writes which return their data.
+    pub fn encode<'b>(&'b self, buf: &'b mut [u8; MAX_PARAMETERS_LEN]) -> (u8, &'b [u8]) {
+        let (opcode, len) = match *self {
+            Command::DriverOutputControl(max_gate_line, scanning) => {
+                let [lower, upper] = max_gate_line.to_le_bytes();
+                (0x01, put(buf, &[lower, upper, scanning]))
+            }
+            Command::GateDrivingVoltage(voltage) => (0x03, put(buf, &[voltage.code()])),
+            Command::SourceDrivingVoltage(voltage) => (0x04, put(buf, &voltage.data())),
+            Command::BoosterSoftStartControl(inrush) => {
+                // First four bytes are always the same as per datasheet page 24
+                (0x0C, put(buf, &[0xAE, 0xC7, 0xC3, 0xC0, inrush as u8]))
+            }
+            Command::DeepSleepMode(mode) => (0x10, put(buf, &[mode as u8])),
+            Command::DataEntryMode(data_entry_mode, increment_axis) => {
+                let setting = ((increment_axis as u8) << 2) | data_entry_mode as u8;
+                (0x11, put(buf, &[setting]))
+            }
+            Command::SoftwareReset => (0x12, 0),
+            Command::TemperatureSensor(sensor) => (0x18, put(buf, &[sensor as u8])),
+            Command::WriteTemperature(temperature) => (0x1A, put(buf, &temperature.to_register())),
+            Command::ReadTemperature => (0x1B, 0),
+            Command::UpdateDisplay => (0x20, 0),
+            Command::UpdateDisplayOption1(black_and_white_option, red_option) => {
+                let options = ((red_option as u8) << 4) | black_and_white_option as u8;
+                (0x21, put(buf, &[options]))
+            }
+            Command::UpdateDisplayOption2(sequence) => (0x22, put(buf, &[sequence.bits()])),
+            Command::WriteRamBlackAndWhite(data) => return (0x24, data),
+            Command::WriteRamRed(data) => return (0x26, data),
+            Command::ReadRam => (0x27, 0),
+            Command::WriteVcom(voltage) => (0x2C, put(buf, &[voltage.code()])),
+            Command::ReadOtpDisplayOption => (0x2D, 0),
+            Command::ReadUserId => (0x2E, 0),
+            Command::ReadStatus => (0x2F, 0),
+            Command::WriteLut(lut) => return (0x32, lut),
+            Command::BorderWaveformControl(vdb_option, fixed_level_setting, transition_setting) => {
+                let setting = ((vdb_option as u8) << 6)
+                    | ((fixed_level_setting as u8) << 4)
+                    | (transition_setting as u8);
+                (0x3C, put(buf, &[setting]))
+            }
+            Command::ReadRamOption(plane) => (0x41, put(buf, &[plane as u8])),
+            Command::RamXAddress(start, end) => (0x44, put(buf, &window(start, end))),
+            Command::RamYAddress(start, end) => (0x45, put(buf, &window(start, end))),
+            Command::AutoWriteRedRamRegularPattern(value) => (0x46, put(buf, &[value])),
+            Command::AutoWriteBlackAndWhiteRamRegularPattern(value) => (0x47, put(buf, &[value])),
+            Command::RamXCount(offset) => (0x4E, put(buf, &offset.to_le_bytes())),
+            Command::RamYCount(offset) => (0x4F, put(buf, &offset.to_le_bytes())),
+            Command::Nop => (0x7F, 0),
+        };
 
-    fn update_display_option1(
-        &mut self,
-        black_and_white_option: RamOption,
-        red_option: RamOption,
-    ) -> Result<(), SPI::Error>;
+        (opcode, &buf[..len])
+    }
 
-    fn update_display_option2(&mut self, option: u8) -> Result<(), SPI::Error>;
+    /// The opcode of the command, as returned by [Command::encode]
+    pub fn opcode(&self) -> u8 {
+        self.encode(&mut [0u8; MAX_PARAMETERS_LEN]).0
+    }
 
-    fn reset_hardware<D: embedded_hal::delay::DelayNs>(&mut self, delay: &mut D);
+    /// Decode a command from its opcode and the parameter bytes sent after it.
+    ///
+    /// Returns `None` if the opcode is not known, or the parameters are not valid for it.
+    pub fn decode(opcode: u8, parameters: &'a [u8]) -> Option<Self> {
+        let command = match (opcode, parameters) {
+            (0x01, &[lower, upper, scanning]) => {
+                Command::DriverOutputControl(u16::from_le_bytes([lower, upper]), scanning)
+            }
+            (0x03, &[code]) => Command::GateDrivingVoltage(GateVoltage::from_code(code)?),
+            (0x04, &[vsh1, vsh2, vsl]) => {
+                Command::SourceDrivingVoltage(SourceVoltage::from_data([vsh1, vsh2, vsl])?)
+            }
+            (0x0C, &[0xAE, 0xC7, 0xC3, 0xC0, inrush]) => {
+                Command::BoosterSoftStartControl(BoosterInrush::from_bits(inrush)?)
+            }
+            (0x10, &[mode]) => Command::DeepSleepMode(DeepSleepMode::from_bits(mode)?),
+            (0x11, &[setting]) if setting >> 3 == 0 => Command::DataEntryMode(
+                DataEntryMode::from_bits(setting & 0b11)?,
+                IncrementAxis::from_bits(setting >> 2)?,
+            ),
+            (0x12, []) => Command::SoftwareReset,
+            (0x18, &[sensor]) => Command::TemperatureSensor(TemperatureSensor::from_bits(sensor)?),
+            (0x1A, &[upper, lower]) => {
+                Command::WriteTemperature(Celsius::from_register([upper, lower]))
+            }
+            (0x1B, []) => Command::ReadTemperature,
+            (0x20, []) => Command::UpdateDisplay,
+            (0x21, &[options]) => Command::UpdateDisplayOption1(
+                RamOption::from_bits(options & 0x0F)?,
+                RamOption::from_bits(options >> 4)?,
+            ),
+            (0x22, &[sequence]) => {
+                Command::UpdateDisplayOption2(UpdateSequence::from_bits(sequence))
+            }
+            (0x24, data) => Command::WriteRamBlackAndWhite(data),
+            (0x26, data) => Command::WriteRamRed(data),
+            (0x27, []) => Command::ReadRam,
+            (0x2C, &[code]) => Command::WriteVcom(VcomVoltage::from_code(code)?),
+            (0x2D, []) => Command::ReadOtpDisplayOption,
+            (0x2E, []) => Command::ReadUserId,
+            (0x2F, []) => Command::ReadStatus,
+            (0x32, lut) => Command::WriteLut(lut.try_into().ok()?),
+            (0x3C, &[setting]) => Command::BorderWaveformControl(
+                WaveformVDBOption::from_bits(setting >> 6)?,
+                VDBFixedLevelSetting::from_bits((setting >> 4) & 0b11)?,
+                VDBGSTransitionSetting::from_bits(setting & 0b11)?,
+            ),
+            (0x41, &[plane]) => Command::ReadRamOption(RamPlane::from_bits(plane)?),
+            (0x44, &[start_lower, start_upper, end_lower, end_upper]) => Command::RamXAddress(
+                u16::from_le_bytes([start_lower, start_upper]),
+                u16::from_le_bytes([end_lower, end_upper]),
+            ),
+            (0x45, &[start_lower, start_upper, end_lower, end_upper]) => Command::RamYAddress(
+                u16::from_le_bytes([start_lower, start_upper]),
+                u16::from_le_bytes([end_lower, end_upper]),
+            ),
+            (0x46, &[value]) => Command::AutoWriteRedRamRegularPattern(value),
+            (0x47, &[value]) => Command::AutoWriteBlackAndWhiteRamRegularPattern(value),
+            (0x4E, &[lower, upper]) => Command::RamXCount(u16::from_le_bytes([lower, upper])),
+            (0x4F, &[lower, upper]) => Command::RamYCount(u16::from_le_bytes([lower, upper])),
+            (0x7F, []) => Command::Nop,
+            _ => return None,
+        };
 
-    fn reset_software(&mut self) -> Result<(), SPI::Error>;
+        Some(command)
+    }
+}
 
-    fn set_border_waveform_control(
-        &mut self,
-        vdb_option: WaveformVDBOption,
-        fixed_level_setting: VDBFixedLevelSetting,
-        transition_setting: VDBGSTransitionSetting,
-    ) -> Result<(), SPI::Error>;
+/// Copy parameters into the encode buffer, returning their length
+fn put(buf: &mut [u8], parameters: &[u8]) -> usize {
+    buf[..parameters.len()].copy_from_slice(parameters);
+    parameters.len()
+}
 
-    fn set_temperature_sensor(&mut self, sensor: TemperatureSensor) -> Result<(), SPI::Error>;
+/// Returns the parameters of a RAM window command.
+/// Start and end are 10-bit, bit ranges 11-16 of the end are discarded.
+fn window(start: u16, end: u16) -> [u8; 4] {
+    let [start_lower, start_upper] = start.to_le_bytes();
+    let [end_lower, end_upper] = end.to_le_bytes();
 
-    fn set_booster_soft_start_control(&mut self, inrush: BoosterInrush) -> Result<(), SPI::Error>;
+    [start_lower, start_upper, end_lower, end_upper & 0b00111111]
 }
 
-/// A command that can be issued to the SSD1677 controller
-impl<SPI, OUT, IN> DisplayCommands<SPI> for Interface4Pin<SPI, OUT, IN>
-where
-    SPI: embedded_hal::spi::SpiDevice,
-    OUT: embedded_hal::digital::OutputPin,
-    IN: embedded_hal::digital::InputPin,
-{
+/// The commands implemented on the display
+///
+/// Commands report errors using the [DisplayInterface::Error] of the interface they are sent
+/// over. Every command is sent with [DisplayInterface::execute], so the bytes sent are those of
+/// [Command::encode], and the commands are available on any [DisplayInterface], including custom
+/// transports.
+pub trait DisplayCommands: DisplayInterface {
     /// Set the MUX of gate lines, scanning sequence and direction
     fn set_driver_output_control(
         &mut self,
         max_gate_lines: u16,
         scanning_sequence_and_direction: u8,
-    ) -> Result<(), SPI::Error> {
-        self.send_command(0x01)?;
-        let [upper, lower] = max_gate_lines.to_le_bytes();
-        self.send_data(&[upper, lower, scanning_sequence_and_direction])?;
-
-        Ok(())
+    ) -> Result<(), Self::Error> {
+        self.execute(Command::DriverOutputControl(
+            max_gate_lines,
+            scanning_sequence_and_direction,
+        ))
     }
 
-    fn set_driver_output_control_from_width(&mut self, width: u16) -> Result<(), SPI::Error> {
+    fn set_driver_output_control_from_width(&mut self, width: u16) -> Result<(), Self::Error> {
         // This command set is based on the example code for the STM32 from here:
         // https://www.good-display.com/product/457.html
-        self.send_command(0x01)?;
-        self.send_data(&[((width - 1) % 256).try_into().unwrap()])?;
-        self.send_data(&[((width - 1) / 256).try_into().unwrap()])?;
-        self.send_data(&[0x02])?;
-
-        Ok(())
+        self.execute(Command::DriverOutputControl(width - 1, 0x02))
     }
 
     /// Define the data entry mode settings
@@ -202,69 +716,46 @@ where
         &mut self,
         data_entry_mode: DataEntryMode,
         increment_axis: IncrementAxis,
-    ) -> Result<(), SPI::Error> {
-        // Send the config command
-        self.send_command(0x11)?;
-
-        // Structure the config data
-        let config_option: u8 = ((increment_axis as u8) << 2) | data_entry_mode as u8;
-
-        // Send the config
-        self.send_data(&[config_option])?;
-
-        Ok(())
+    ) -> Result<(), Self::Error> {
+        self.execute(Command::DataEntryMode(data_entry_mode, increment_axis))
     }
 
     /// Write data to the black and white RAM buffer
-    fn write_ram_black_and_white(&mut self, data: &[u8]) -> Result<(), SPI::Error> {
-        self.send_command(0x24)?;
-        self.send_data(data)?;
-        Ok(())
+    fn write_ram_black_and_white(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        self.execute(Command::WriteRamBlackAndWhite(data))
     }
 
     /// Write data to the red RAM buffer
-    fn write_ram_red(&mut self, data: &[u8]) -> Result<(), SPI::Error> {
-        self.send_command(0x26)?;
-        self.send_data(data)?;
-        Ok(())
+    fn write_ram_red(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        self.execute(Command::WriteRamRed(data))
     }
 
     /// Fill the red RAM buffer with a single value
-    fn auto_write_ram_red_regular_pattern(&mut self, value: u8) -> Result<(), SPI::Error> {
-        self.send_command(0x46)?;
-        self.send_data(&[value])?;
-        Ok(())
+    fn auto_write_ram_red_regular_pattern(&mut self, value: u8) -> Result<(), Self::Error> {
+        self.execute(Command::AutoWriteRedRamRegularPattern(value))
     }
 
     /// Fill the black and white RAM buffer with a single value
     fn auto_write_ram_black_and_white_regular_pattern(
         &mut self,
         value: u8,
-    ) -> Result<(), SPI::Error> {
-        self.send_command(0x47)?;
-        self.send_data(&[value])?;
-        Ok(())
+    ) -> Result<(), Self::Error> {
+        self.execute(Command::AutoWriteBlackAndWhiteRamRegularPattern(value))
     }
 
     /// Set the current X axis count
-    fn set_ram_x_count(&mut self, offset: u16) -> Result<(), SPI::Error> {
-        self.send_command(0x4E)?;
-        self.send_data(&offset.to_le_bytes())?;
-        Ok(())
+    fn set_ram_x_count(&mut self, offset: u16) -> Result<(), Self::Error> {
+        self.execute(Command::RamXCount(offset))
     }
 
     /// Set the current Y axis count
-    fn set_ram_y_count(&mut self, offset: u16) -> Result<(), SPI::Error> {
-        self.send_command(0x4F)?;
-        self.send_data(&offset.to_le_bytes())?;
-        Ok(())
+    fn set_ram_y_count(&mut self, offset: u16) -> Result<(), Self::Error> {
+        self.execute(Command::RamYCount(offset))
     }
 
-    fn refresh_display(&mut self) -> Result<(), SPI::Error> {
-        // Send the refesh command
-        self.send_command(0x20)?;
-        self.busy_wait();
-        Ok(())
+    fn refresh_display(&mut self) -> Result<(), Self::Error> {
+        // Busy waits until the refresh is done
+        self.execute(Command::UpdateDisplay)
     }
 
     /// Specify the start/end positions of the window address in the X direction by an address unit
@@ -272,18 +763,8 @@ where
     ///
     /// # Note
     /// Start any end values are 10-bit, bit ranges 11-16 will be discarded.
-    fn set_ram_x_address(&mut self, start: u16, end: u16) -> Result<(), SPI::Error> {
-        // Split the input value to bytes
-        let [start_hi, start_lo] = start.to_le_bytes();
-        let [end_hi, end_lo] = end.to_le_bytes();
-
-        // Create the data
-        let data = [start_hi, start_lo, end_hi, (end_lo & 0b00111111)];
-
-        self.send_command(0x44)?;
-        self.send_data(&data)?;
-
-        Ok(())
+    fn set_ram_x_address(&mut self, start: u16, end: u16) -> Result<(), Self::Error> {
+        self.execute(Command::RamXAddress(start, end))
     }
 
     /// Specify the start/end positions of the window address in the Y direction by an address unit
@@ -291,22 +772,16 @@ where
     ///
     /// # Note
     /// Start any end values are 10-bit, bit ranges 11-16 will be discarded.
-    fn set_ram_y_address(&mut self, start: u16, end: u16) -> Result<(), SPI::Error> {
-        // Split the input value to bytes
-        let [start_hi, start_lo] = start.to_le_bytes();
-        let [end_hi, end_lo] = end.to_le_bytes();
-
-        // Create the data
-        let data = [start_hi, start_lo, end_hi, (end_lo & 0b00111111)];
-
-        self.send_command(0x45)?;
-        self.send_data(&data)?;
-
-        Ok(())
+    fn set_ram_y_address(&mut self, start: u16, end: u16) -> Result<(), Self::Error> {
+        self.execute(Command::RamYAddress(start, end))
     }
 
     /// Set the start and end RAM addresses for both X and Y based on the display dimentions given
-    fn set_ram_address_based_on_size(&mut self, width: u16, height: u16) -> Result<(), SPI::Error> {
+    fn set_ram_address_based_on_size(
+        &mut self,
+        width: u16,
+        height: u16,
+    ) -> Result<(), Self::Error> {
         self.set_ram_x_address(0, height - 1)?;
         self.set_ram_y_address(0, width - 1)?;
 
@@ -315,60 +790,60 @@ where
 
     // No operation instruction, does nothing.
     // It can be used to terminate Frame Memory Write or Read commands
-    fn nop(&mut self) -> Result<(), SPI::Error> {
-        self.send_command(0x7F)?;
+    fn nop(&mut self) -> Result<(), Self::Error> {
+        self.execute(Command::Nop)
+    }
 
-        Ok(())
+    /// Set the gate driving voltage, see [GateVoltage] for the valid values
+    fn set_gate_driving_voltage(&mut self, voltage: GateVoltage) -> Result<(), Self::Error> {
+        self.execute(Command::GateDrivingVoltage(voltage))
     }
 
-    /// Set the gate driving voltage
-    /// Valid values are between 12 and 20 in increments of 0.5 volts
-    fn set_gate_driving_voltage(&mut self, voltage: f32) -> Result<(), SPI::Error> {
-        // Validate that it is within range
-        // If not, set the voltage to the POR value of 20V
-        let value: u8 = match voltage {
-            12.0 => 0x07,
-            12.5 => 0x08,
-            13.0 => 0x09,
-            13.5 => 0x0A,
-            14.0 => 0x0B,
-            14.5 => 0x0C,
-            15.0 => 0x0D,
-            15.5 => 0x0E,
-            16.0 => 0x0F,
-            16.5 => 0x10,
-            17.0 => 0x11,
-            17.5 => 0x12,
-            18.0 => 0x13,
-            18.5 => 0x14,
-            19.0 => 0x15,
-            19.5 => 0x16,
-            20.0 => 0x17,
-            _ => 0, // POR value, also 20V
-        };
+    /// Set the source driving voltage, see [SourceVoltage] for the valid values
+    ///
+    /// # NOTE
+    /// VSH1 must be larger than or equal to VSH2, this is validated by [SourceVoltage]
+    fn set_source_driving_voltage(&mut self, voltage: SourceVoltage) -> Result<(), Self::Error> {
+        self.execute(Command::SourceDrivingVoltage(voltage))
+    }
 
-        self.send_command(0x03)?;
-        self.send_data(&[value])?;
+    /// Write the VCOM register, see [VcomVoltage] for the valid values
+    fn write_vcom(&mut self, voltage: VcomVoltage) -> Result<(), Self::Error> {
+        self.execute(Command::WriteVcom(voltage))
+    }
 
-        Ok(())
+    /// Read the display options programmed into OTP
+    fn read_otp_display_option(&mut self) -> Result<OtpDisplayOption, Self::Error> {
+        let mut data = [0u8; OtpDisplayOption::LEN];
+        self.read_command(Command::ReadOtpDisplayOption.opcode(), &mut [], &mut data)?;
+
+        Ok(OtpDisplayOption::from_bytes(&data))
     }
 
-    /// Set the source driving voltage
-    ///
-    /// # NOTE
-    /// VSH1 must be larger than VSH2
-    /// Valid voltage range:
-    ///     VSH1 = 9 to 17 (increments of 0.2)
-    ///     VSH2 = 2.4 to 17 (increments of 0.1 between 2.4 and 9 V, increments of 0.2 from
-    ///     thereon)
-    ///     VSL = -9 to -17 (increments of 0.5)
-    fn set_source_driving_voltage(
-        &mut self,
-        vsh1_voltage: f32,
-        vsh2_voltage: f32,
-        vsl_voltage: f32,
-    ) -> Result<(), SPI::Error> {
-        todo!();
+    /// Read RAM from the current RAM address counters.
+    /// The counters advance as with a write, so consecutive reads continue where the last ended.
+    fn read_ram(&mut self, plane: RamPlane, data: &mut [u8]) -> Result<(), Self::Error> {
+        // Select the RAM to read
+        self.execute(Command::ReadRamOption(plane))?;
+
+        // The first byte read after the command is a dummy byte
+        self.read_command(Command::ReadRam.opcode(), &mut [0u8], data)
+    }
+
+    /// Read the status bits of the controller
+    fn read_status(&mut self) -> Result<Status, Self::Error> {
+        let mut data = [0u8];
+        self.read_command(Command::ReadStatus.opcode(), &mut [], &mut data)?;
+
+        Ok(Status::from_byte(data[0]))
+    }
+
+    /// Read the user ID programmed into OTP
+    fn read_user_id(&mut self) -> Result<[u8; USER_ID_LEN], Self::Error> {
+        let mut data = [0u8; USER_ID_LEN];
+        self.read_command(Command::ReadUserId.opcode(), &mut [], &mut data)?;
+
+        Ok(data)
     }
 
     /// Set RAM content options for update display command.
@@ -376,51 +851,40 @@ where
         &mut self,
         black_and_white_option: RamOption,
         red_option: RamOption,
-    ) -> Result<(), SPI::Error> {
-        // Create the data value
-        let data: u8 = (red_option as u8 & 0b1111) << 4     //Set the red option
-        | (black_and_white_option as u8 & 0b1111); // Set the BW opiton
-
-        // Send the command and data
-        self.send_command(0x21)?;
-        self.send_data(&[data])?;
-
-        Ok(())
+    ) -> Result<(), Self::Error> {
+        self.execute(Command::UpdateDisplayOption1(
+            black_and_white_option,
+            red_option,
+        ))
     }
 
     /// Set display update sequence option
-    /// See datasheet entry for what values mean
-    fn update_display_option2(&mut self, option: u8) -> Result<(), SPI::Error> {
-        self.send_command(0x22)?;
-        self.send_data(&[option])?;
+    /// See [UpdateSequence] for the steps that can be run
+    fn update_display_option2(&mut self, sequence: UpdateSequence) -> Result<(), Self::Error> {
+        self.execute(Command::UpdateDisplayOption2(sequence))
+    }
 
-        Ok(())
+    /// Write a waveform LUT to the controller, replacing the LUT loaded from OTP
+    fn write_lut(&mut self, lut: &Lut) -> Result<(), Self::Error> {
+        self.execute(Command::WriteLut(&lut.to_bytes()))
     }
 
     /// Perform a hardware reset
-    fn reset_hardware<D: embedded_hal::delay::DelayNs>(&mut self, delay: &mut D) {
-        use crate::interface::RESET_DELAY_MS;
-
-        // Disable the display, the wait for the controller to catch up
-        self.reset_pin.set_low().unwrap();
-        delay.delay_ms(RESET_DELAY_MS.into());
-        // Enable the display, the wait for the controller to catch up
-        self.reset_pin.set_high().unwrap();
-        delay.delay_ms(RESET_DELAY_MS.into());
+    fn reset_hardware<D: embedded_hal::delay::DelayNs>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<(), Self::Error> {
+        // Toggle the reset pin
+        self.reset(delay)
     }
 
     /// Perform a software reset.
     /// This resets all parameters except deep sleep mode to their default values.
     /// RAM content is not affected.
     /// BUSY will be high while reset is in progress
-    fn reset_software(&mut self) -> Result<(), SPI::Error> {
-        // Tell the device to soft reset
-        self.send_command(0x12)?;
-
-        // Wait for the soft reset to be over
-        self.busy_wait();
-
-        Ok(())
+    fn reset_software(&mut self) -> Result<(), Self::Error> {
+        // Busy waits until the soft reset is over
+        self.execute(Command::SoftwareReset)
     }
 
     /// Select border waveform for VBD
@@ -429,61 +893,149 @@ where
         vdb_option: WaveformVDBOption,
         fixed_level_setting: VDBFixedLevelSetting,
         transition_setting: VDBGSTransitionSetting,
-    ) -> Result<(), SPI::Error> {
-        self.send_command(0x3C)?;
-
-        // Create the data packet
-        let data = ((vdb_option as u8) << 6)
-            | ((fixed_level_setting as u8) << 4)
-            | (transition_setting as u8);
-
-        self.send_data(&[data])?;
-
-        Ok(())
+    ) -> Result<(), Self::Error> {
+        self.execute(Command::BorderWaveformControl(
+            vdb_option,
+            fixed_level_setting,
+            transition_setting,
+        ))
     }
 
     /// Specify which temperature sensor the display uses
-    fn set_temperature_sensor(&mut self, sensor: TemperatureSensor) -> Result<(), SPI::Error> {
-        self.send_command(0x18)?;
-        self.send_data(&[sensor as u8])?;
-        Ok(())
+    fn set_temperature_sensor(&mut self, sensor: TemperatureSensor) -> Result<(), Self::Error> {
+        self.execute(Command::TemperatureSensor(sensor))
     }
 
-    /// Control the inrush current for the booster
-    fn set_booster_soft_start_control(&mut self, inrush: BoosterInrush) -> Result<(), SPI::Error> {
-        // Frist four bytes are always the same as per datasheet page 24
-        // Last bytes depend on inrush mode, these are defined in the enum
-        let control_value: [u8; 5] = [0xAE, 0xC7, 0xC3, 0xC0, inrush as u8];
-
-        self.send_command(0x0C)?;
-        self.send_data(&control_value)?;
-
-        Ok(())
+    /// Write the temperature register, used to select the waveform when loading the LUT
+    fn write_temperature(&mut self, temperature: Celsius) -> Result<(), Self::Error> {
+        self.execute(Command::WriteTemperature(temperature))
     }
 
-    /*
-
-
+    /// Read the temperature register.
+    /// The register is only updated when the display update sequence loads the temperature.
+    fn read_temperature(&mut self) -> Result<Celsius, Self::Error> {
+        let mut register = [0u8; 2];
+        self.read_command(Command::ReadTemperature.opcode(), &mut [], &mut register)?;
+        Ok(Celsius::from_register(register))
+    }
 
+    /// Control the inrush current for the booster
+    fn set_booster_soft_start_control(&mut self, inrush: BoosterInrush) -> Result<(), Self::Error> {
+        self.execute(Command::BoosterSoftStartControl(inrush))
+    }
 
-    /// Set the deep sleep mode
-    DeepSleepMode(DeepSleepMode),
+    /// Enter or leave deep sleep mode.
+    /// The busy pin stays high while the controller is in deep sleep, and the controller
+    /// can only be woken by a hardware reset.
+    fn deep_sleep(&mut self, mode: DeepSleepMode) -> Result<(), Self::Error> {
+        // Does not wait for the busy pin, it will not go low again while sleeping
+        self.execute(Command::DeepSleepMode(mode))
+    }
+}
 
-    /// Set the data entry mode and the increment axis
-    DataEntryMode(DataEntryMode, IncrementAxis),
+/// The commands are sent over any interface to the controller
+impl<I> DisplayCommands for I where I: DisplayInterface {}
 
+#[cfg(test)]
+mod tests {
+    use super::*;
 
+    /// Encode a command, then decode it from the opcode and parameters
+    fn round_trip(command: Command<'_>) {
+        let mut buf = [0u8; MAX_PARAMETERS_LEN];
+        let (opcode, parameters) = command.encode(&mut buf);
 
-    /// Write to temperature sensor register
-    /// Please note that the register is 12-bit
-    WriteTemperatureSensorControl(u16),
+        assert_eq!(Command::decode(opcode, parameters), Some(command));
+        assert_eq!(command.opcode(), opcode);
+    }
 
-    /// Read temperature sensor control register
-    ReadTemperatureSensorControl,
+    #[test]
+    fn every_command_round_trips() {
+        let data = [0x00, 0x5A, 0xFF];
+        let lut = [0xA5; LUT_LEN];
+
+        let commands = [
+            Command::DriverOutputControl(479, 0x02),
+            Command::GateDrivingVoltage(GateVoltage::new(18_000)),
+            Command::SourceDrivingVoltage(SourceVoltage::new(15_000, 5_000, -15_000)),
+            Command::BoosterSoftStartControl(BoosterInrush::Level1),
+            Command::BoosterSoftStartControl(BoosterInrush::Level2),
+            Command::DeepSleepMode(DeepSleepMode::Normal),
+            Command::DeepSleepMode(DeepSleepMode::PreserveRAM),
+            Command::DeepSleepMode(DeepSleepMode::DiscardRAM),
+            Command::DataEntryMode(
+                DataEntryMode::IncrementXIncrementY,
+                IncrementAxis::Horizontal,
+            ),
+            Command::DataEntryMode(DataEntryMode::DecrementXDecrementY, IncrementAxis::Vertical),
+            Command::SoftwareReset,
+            Command::TemperatureSensor(TemperatureSensor::Internal),
+            Command::TemperatureSensor(TemperatureSensor::External),
+            Command::WriteTemperature(Celsius::from_degrees(-25)),
+            Command::WriteTemperature(Celsius::from_sixteenths(0x1F5)),
+            Command::ReadTemperature,
+            Command::UpdateDisplay,
+            Command::UpdateDisplayOption1(RamOption::Normal, RamOption::Bypass),
+            Command::UpdateDisplayOption1(RamOption::Invert, RamOption::Normal),
+            Command::UpdateDisplayOption2(UpdateSequence::from_bits(0xF7)),
+            Command::WriteRamBlackAndWhite(&data),
+            Command::WriteRamBlackAndWhite(&[]),
+            Command::WriteRamRed(&data),
+            Command::ReadRam,
+            Command::WriteVcom(VcomVoltage::new(-1_500)),
+            Command::ReadOtpDisplayOption,
+            Command::ReadUserId,
+            Command::ReadStatus,
+            Command::WriteLut(&lut),
+            Command::BorderWaveformControl(
+                WaveformVDBOption::Transition,
+                VDBFixedLevelSetting::VSS,
+                VDBGSTransitionSetting::LUT1,
+            ),
+            Command::BorderWaveformControl(
+                WaveformVDBOption::HiZ,
+                VDBFixedLevelSetting::VSH2,
+                VDBGSTransitionSetting::LUT3,
+            ),
+            Command::ReadRamOption(RamPlane::BlackAndWhite),
+            Command::ReadRamOption(RamPlane::Red),
+            Command::RamXAddress(0, 799),
+            Command::RamYAddress(479, 0),
+            Command::AutoWriteRedRamRegularPattern(0xF7),
+            Command::AutoWriteBlackAndWhiteRamRegularPattern(0x00),
+            Command::RamXCount(799),
+            Command::RamYCount(0),
+            Command::Nop,
+        ];
+
+        for command in commands {
+            round_trip(command);
+        }
+    }
 
-    /// Activate the display update sequence.
-    /// BUSY will be high while this is in progress.
-    UpdateDisplay,
+    #[test]
+    fn every_decoded_parameter_round_trips() {
+        // Every command a single parameter byte decodes to survives encoding and decoding again
+        let opcodes = [
+            0x03, 0x10, 0x11, 0x18, 0x21, 0x22, 0x2C, 0x3C, 0x41, 0x46, 0x47,
+        ];
+
+        for opcode in opcodes {
+            for parameter in 0..=u8::MAX {
+                if let Some(command) = Command::decode(opcode, &[parameter]) {
+                    round_trip(command);
+                }
+            }
+        }
+    }
 
-    */
+    #[test]
+    fn rejects_invalid_parameters() {
+        assert_eq!(Command::decode(0x01, &[0xDF, 0x01]), None);
+        assert_eq!(Command::decode(0x0C, &[0x00, 0x00, 0x00, 0x00, 0x40]), None);
+        assert_eq!(Command::decode(0x11, &[0b1011]), None);
+        assert_eq!(Command::decode(0x12, &[0x00]), None);
+        assert_eq!(Command::decode(0x32, &[0x00; LUT_LEN - 1]), None);
+        assert_eq!(Command::decode(0xFF, &[]), None);
+    }
 }
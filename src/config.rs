@@ -5,14 +5,116 @@
 //! users to specify the dimensions and rotation of the display, ensuring that all
 //! necessary parameters are set before creating a [Config].
 //!
+//! Panels built on the SSD1677 differ in their border, booster and voltage setup. The commands
+//! for these are sent as an init sequence, a static table of [InitStep]s set with
+//! [Builder::init_sequence]:
+//!
+//! ```
+//! use ssd1677::command::{
+//!     BoosterInrush, Command, DataEntryMode, IncrementAxis, TemperatureSensor,
+//!     VDBFixedLevelSetting, VDBGSTransitionSetting, WaveformVDBOption,
+//! };
+//! use ssd1677::config::{InitStep, LOAD_LUT_AND_DISPLAY};
+//! use ssd1677::voltage::GateVoltage;
+//! use ssd1677::{ConfigBuilder, Dimensions};
+//!
+//! static MY_PANEL_INIT: &[InitStep] = &[
+//!     InitStep::Command(Command::AutoWriteBlackAndWhiteRamRegularPattern(0xFF)),
+//!     InitStep::Command(Command::BoosterSoftStartControl(BoosterInrush::Level2)),
+//!     InitStep::Command(Command::GateDrivingVoltage(GateVoltage::new(18_000))),
+//!     InitStep::DriverOutputControl(0x00),
+//!     InitStep::Command(Command::DataEntryMode(
+//!         DataEntryMode::IncrementXIncrementY,
+//!         IncrementAxis::Horizontal,
+//!     )),
+//!     InitStep::RamSize,
+//!     InitStep::Command(Command::BorderWaveformControl(
+//!         WaveformVDBOption::Fixed,
+//!         VDBFixedLevelSetting::VSS,
+//!         VDBGSTransitionSetting::LUT0,
+//!     )),
+//!     InitStep::Command(Command::TemperatureSensor(TemperatureSensor::Internal)),
+//!     InitStep::LoadLut(LOAD_LUT_AND_DISPLAY),
+//! ];
+//!
+//! let config = ConfigBuilder::new()
+//!     .dimensions(Dimensions { rows: 480, cols: 800 })
+//!     .init_sequence(MY_PANEL_INIT)
+//!     .build()
+//!     .unwrap();
+//! ```
+//!
 //! [BasicDisplay]: basic_display/struct.BasicDisplay.html
 use crate::basic_display::{self, Dimensions, Rotation};
+use crate::command::{
+    Command, DataEntryMode, DisplayMode, IncrementAxis, TemperatureSensor, UpdateSequence,
+    VDBFixedLevelSetting, VDBGSTransitionSetting, WaveformVDBOption,
+};
+
+/// A step of the init sequence, see [Builder::init_sequence].
+///
+/// Most steps are plain [Command]s. The steps that depend on the dimensions of the display, or
+/// on whether a custom LUT is loaded, are filled in when the sequence is sent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InitStep {
+    /// Send a command as is
+    Command(Command<'static>),
+    /// Set the driver output control for the rows of the display, with the given gate scanning
+    /// sequence and direction
+    DriverOutputControl(u8),
+    /// Set the RAM window to the whole display
+    RamSize,
+    /// Load the waveform LUT.
+    ///
+    /// A custom LUT loaded with
+    /// [BasicDisplay::load_lut](crate::basic_display::BasicDisplay::load_lut) is written to the
+    /// controller. Otherwise the LUT is loaded from OTP by running the display update sequence.
+    LoadLut(UpdateSequence),
+}
+
+/// Display update sequence that loads the LUT from OTP and refreshes the display with the RAM
+pub const LOAD_LUT_AND_DISPLAY: UpdateSequence = UpdateSequence::new()
+    .enable_clock()
+    .enable_analog()
+    .load_temperature()
+    .load_lut()
+    .display(DisplayMode::Mode2)
+    .disable_analog()
+    .disable_clock();
+
+/// The default init sequence, taken from the STM32 example code from GoodDisplay:
+/// <https://www.good-display.com/product/457.html>
+pub const DEFAULT_INIT_SEQUENCE: &[InitStep] = &[
+    // Clear and fill RAM
+    InitStep::Command(Command::AutoWriteBlackAndWhiteRamRegularPattern(0xF7)),
+    InitStep::Command(Command::AutoWriteRedRamRegularPattern(0xF7)),
+    // Set gate driver output
+    InitStep::DriverOutputControl(0x02),
+    // Set the data entry mode
+    InitStep::Command(Command::DataEntryMode(
+        DataEntryMode::IncrementXIncrementY,
+        IncrementAxis::Horizontal,
+    )),
+    // Set the display RAM size
+    InitStep::RamSize,
+    // Set the panel border waveform control
+    InitStep::Command(Command::BorderWaveformControl(
+        WaveformVDBOption::Transition,
+        VDBFixedLevelSetting::VSS,
+        VDBGSTransitionSetting::LUT1,
+    )),
+    // Set temperature sensor
+    InitStep::Command(Command::TemperatureSensor(TemperatureSensor::Internal)),
+    // Load the waveform LUT and refresh the display
+    InitStep::LoadLut(LOAD_LUT_AND_DISPLAY),
+];
 
 /// Builder for constructing a display config
 pub struct Builder {
     dimensions: Option<Dimensions>,
     rotation: Rotation,
     auto_update: bool,
+    init_sequence: &'static [InitStep],
 }
 
 /// Display configuration.
@@ -25,6 +127,7 @@ pub struct Config {
     pub(crate) dimensions: Dimensions,
     pub(crate) rotation: Rotation,
     pub(crate) auto_update: bool,
+    pub(crate) init_sequence: &'static [InitStep],
 }
 
 /// Error returned by invalid Builder configuration.
@@ -39,6 +142,7 @@ impl Default for Builder {
             dimensions: None,
             rotation: Rotation::default(),
             auto_update: true,
+            init_sequence: DEFAULT_INIT_SEQUENCE,
         }
     }
 }
@@ -67,6 +171,8 @@ impl Builder {
     /// # Arguments
     ///
     /// * `dimensions` - The dimensions of the display to be set.
+    // `is_multiple_of` needs Rust 1.87, keep `%` so older compilers are supported
+    #[allow(clippy::manual_is_multiple_of)]
     pub fn dimensions(self, dimensions: Dimensions) -> Self {
         // Validate that we have valid dimensions
         assert!(
@@ -118,6 +224,28 @@ impl Builder {
         }
     }
 
+    /// Set the init sequence of the panel.
+    ///
+    /// The steps are sent in order whenever the display is initialized. The default is
+    /// [DEFAULT_INIT_SEQUENCE]. The updates of the display expect the data entry mode
+    /// [IncrementXIncrementY](DataEntryMode::IncrementXIncrementY) along the
+    /// [Horizontal](IncrementAxis::Horizontal) axis, and the RAM window to cover the whole
+    /// display, so a custom sequence should keep those steps.
+    ///
+    /// When the controller is woken from deep sleep the sequence is sent again to restore the
+    /// registers, leaving out the commands writing RAM, and [InitStep::LoadLut] loads the LUT
+    /// without refreshing the display.
+    ///
+    /// # Arguments
+    ///
+    /// * `sequence` - The steps to send on initialization.
+    pub fn init_sequence(self, sequence: &'static [InitStep]) -> Self {
+        Self {
+            init_sequence: sequence,
+            ..self
+        }
+    }
+
     /// Build the display configuration.
     ///
     /// This method constructs a `Config` instance from the builder. It will fail if the
@@ -126,11 +254,13 @@ impl Builder {
     /// # Returns
     ///
     /// * `Result<Config, BuilderError>` - A result containing the built configuration or an error.
+    #[allow(clippy::unnecessary_lazy_evaluations)]
     pub fn build(self) -> Result<Config, BuilderError> {
         Ok(Config {
             dimensions: self.dimensions.ok_or_else(|| BuilderError {})?,
             rotation: self.rotation,
             auto_update: self.auto_update,
+            init_sequence: self.init_sequence,
         })
     }
 }
@@ -13,12 +13,10 @@
 //!
 //! [DrawTarget]: https://docs.rs/embedded-graphics-core/0.4.0/embedded_graphics_core/draw_target/trait.DrawTarget.html
 //! [embedded-graphics-core]: https://crates.io/crates/embedded-graphics-core
-use crate::basic_display::{BasicDisplay, DisplayUpdateMode, Rotation};
-use crate::command::DisplayCommands;
+use crate::basic_display::{BasicDisplay, DisplayUpdateMode, Region, Rotation};
 use crate::config;
+use crate::export::{buffer_bit, logical_size, Image};
 use crate::interface::DisplayInterface;
-use core::usize;
-use embedded_hal;
 
 #[cfg(feature = "graphics")]
 use embedded_graphics_core::{pixelcolor::BinaryColor, prelude::*};
@@ -27,20 +25,21 @@ use embedded_graphics_core::{pixelcolor::BinaryColor, prelude::*};
 use defmt::*;
 
 /// A display that holds buffers for drawing into and updating the display.
-pub struct Display<'a, I, SPI>
+///
+/// The display keeps track of the region of the buffer that changed since the last update,
+/// so [Display::update_dirty] can send only that region to the controller.
+pub struct Display<'a, I>
 where
-    SPI: embedded_hal::spi::SpiDevice,
-    I: DisplayInterface + DisplayCommands<SPI>,
+    I: DisplayInterface,
 {
-    display: BasicDisplay<I, SPI>, // The underlying display interface
-    bw_buffer: &'a mut [u8],       // The buffer for black and white pixel data
-                                   // TODO: Implement RED support
+    display: BasicDisplay<I>, // The underlying display interface
+    bw_buffer: &'a mut [u8],  // The buffer for black and white pixel data
+    dirty: Option<Region>,    // The region of the buffer changed since the last update
 }
 
-impl<'a, I, SPI> Display<'a, I, SPI>
+impl<'a, I> Display<'a, I>
 where
-    SPI: embedded_hal::spi::SpiDevice,
-    I: DisplayInterface + DisplayCommands<SPI>,
+    I: DisplayInterface,
 {
     /// Creates a new [Display] instance.
     ///
@@ -81,8 +80,15 @@ where
     ///
     /// * `display` - The underlying display instance.
     /// * `bw_buffer` - A mutable reference to the buffer for black and white pixel data.
-    pub fn from_basic_display(display: BasicDisplay<I, SPI>, bw_buffer: &'a mut [u8]) -> Self {
-        Display { display, bw_buffer }
+    pub fn from_basic_display(display: BasicDisplay<I>, bw_buffer: &'a mut [u8]) -> Self {
+        // The controller RAM is unknown, so the entire buffer must be sent on the first update
+        let dirty = Some(full_region(&display));
+
+        Display {
+            display,
+            bw_buffer,
+            dirty,
+        }
     }
 
     /// Update the display by writing the buffer to the controller.
@@ -102,7 +108,95 @@ where
         &mut self,
         mode: DisplayUpdateMode,
     ) -> Result<(), <I as DisplayInterface>::Error> {
-        self.display.update(Some(self.bw_buffer), None, mode)
+        self.display.update(Some(self.bw_buffer), None, mode)?;
+        self.dirty = None;
+
+        Ok(())
+    }
+
+    /// Update the display by writing only the changed part of the buffer to the controller.
+    ///
+    /// Every pixel drawn since the last update is included in the dirty region, see
+    /// [Display::dirty_region]. Only the bytes of the region are sent, as a single write with the
+    /// RAM window restricted to the region, the same as [BasicDisplay::update_region]. This saves
+    /// SPI time and power when only a small part of the display changed.
+    /// If nothing changed since the last update the display is not refreshed.
+    ///
+    /// If the controller RAM may no longer hold the buffer, such as after [BasicDisplay::init] or
+    /// waking from deep sleep, the entire buffer is sent instead, see
+    /// [BasicDisplay::ram_invalid].
+    ///
+    /// # Arguments
+    ///
+    /// * `mode` - The kind of update to perform, see [DisplayUpdateMode] for details.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), <I as DisplayInterface>::Error>` - Returns `Ok(())` on success,
+    ///   or an error if the update fails.
+    pub fn update_dirty(
+        &mut self,
+        mode: DisplayUpdateMode,
+    ) -> Result<(), <I as DisplayInterface>::Error> {
+        // Only the dirty region can be sent while the RAM holds the rest of the buffer
+        if self.display.ram_invalid() {
+            return self.update(mode);
+        }
+
+        let Some(dirty) = self.dirty else {
+            return Ok(());
+        };
+
+        // Send the bytes of the region out of the rows of the buffer
+        let stride = self.cols() as usize / 8;
+        let start = dirty.y as usize * stride + dirty.x as usize / 8;
+        let len = (dirty.height as usize - 1) * stride + dirty.width as usize / 8;
+        let bytes = &self.bw_buffer[start..start + len];
+
+        self.display
+            .write_region(dirty, Some(bytes), None, stride, mode)?;
+        self.dirty = None;
+
+        Ok(())
+    }
+
+    /// Returns the region of the buffer that changed since the last update.
+    ///
+    /// The region is in the native orientation of the controller, with the rotation of the
+    /// display already applied, and aligned to whole bytes. `None` is returned if the buffer did
+    /// not change.
+    pub fn dirty_region(&self) -> Option<Region> {
+        self.dirty
+    }
+
+    /// Mark the byte at `index` in the buffer as changed
+    fn mark_dirty(&mut self, index: usize) {
+        // Find the position of the byte in the native orientation
+        let row_len = self.cols() as usize / 8;
+        let x = ((index % row_len) * 8) as u16;
+        let y = (index / row_len) as u16;
+
+        self.dirty = Some(match self.dirty {
+            None => Region {
+                x,
+                y,
+                width: 8,
+                height: 1,
+            },
+            Some(region) => {
+                let x_start = region.x.min(x);
+                let y_start = region.y.min(y);
+                let x_end = (region.x + region.width).max(x + 8);
+                let y_end = (region.y + region.height).max(y + 1);
+
+                Region {
+                    x: x_start,
+                    y: y_start,
+                    width: x_end - x_start,
+                    height: y_end - y_start,
+                }
+            }
+        });
     }
 
     #[cfg(not(feature = "graphics"))]
@@ -124,6 +218,8 @@ where
             *byte = fill_value;
         }
 
+        self.dirty = Some(full_region(&self.display));
+
         // Refresh the display
         self.update(DisplayUpdateMode::Slow)
     }
@@ -149,6 +245,8 @@ where
             *byte = fill_value;
         }
 
+        self.dirty = Some(full_region(&self.display));
+
         // Refresh the display if auto_update is enabled
         if self.display.config.auto_update {
             self.update(DisplayUpdateMode::Slow)
@@ -195,47 +293,60 @@ where
 
         // TODO: Add runtime check to validate that we are in bounds
 
+        self.mark_dirty(index);
+
         // Set the value in the display buffer
         match color {
             BinaryColor::On => {
-                self.bw_buffer.as_mut()[index] &= !bit;
+                self.bw_buffer[index] &= !bit;
             }
             BinaryColor::Off => {
-                self.bw_buffer.as_mut()[index] |= bit;
+                self.bw_buffer[index] |= bit;
             }
         }
     }
 }
 
-impl<'a, I, SPI> core::ops::Deref for Display<'a, I, SPI>
+impl<'a, I> core::ops::Deref for Display<'a, I>
 where
-    SPI: embedded_hal::spi::SpiDevice,
-    I: DisplayInterface + DisplayCommands<SPI>,
+    I: DisplayInterface,
 {
-    type Target = BasicDisplay<I, SPI>;
+    type Target = BasicDisplay<I>;
 
     /// Dereference to access the underlying [BasicDisplay] instance.
     ///
     /// This allows for direct access to the methods and properties of the
     /// [BasicDisplay] struct.
-    fn deref(&self) -> &BasicDisplay<I, SPI> {
+    fn deref(&self) -> &BasicDisplay<I> {
         &self.display
     }
 }
 
-impl<'a, I, SPI> core::ops::DerefMut for Display<'a, I, SPI>
+impl<'a, I> core::ops::DerefMut for Display<'a, I>
 where
-    SPI: embedded_hal::spi::SpiDevice,
-    I: DisplayInterface + DisplayCommands<SPI>,
+    I: DisplayInterface,
 {
     /// Mutably dereference to access the underlying [BasicDisplay] instance.
     ///
     /// This allows for modification of the [BasicDisplay] struct and its properties.
-    fn deref_mut(&mut self) -> &mut BasicDisplay<I, SPI> {
+    fn deref_mut(&mut self) -> &mut BasicDisplay<I> {
         &mut self.display
     }
 }
 
+/// Returns a [Region] covering the entire display
+fn full_region<I>(display: &BasicDisplay<I>) -> Region
+where
+    I: DisplayInterface,
+{
+    Region {
+        x: 0,
+        y: 0,
+        width: display.cols(),
+        height: display.rows(),
+    }
+}
+
 /// Calculate the pixel index and bit mask for a given pixel position based on the rotation.
 ///
 /// This function determines the appropriate index in the buffer and the bit mask
@@ -253,11 +364,11 @@ where
 /// # Returns
 ///
 /// * `(u32, u8)` - A tuple containing the index in the buffer and the bit mask for the pixel.
-fn rotation(x: u32, y: u32, width: u32, height: u32, rotation: Rotation) -> (u32, u8) {
+pub(crate) fn rotation(x: u32, y: u32, width: u32, height: u32, rotation: Rotation) -> (u32, u8) {
     // Calculate the value of x depending on the rotation
     let x = match rotation {
-        Rotation::Rotate0 | Rotation::Rotate180 => width as u32 - x,
-        Rotation::Rotate90 | Rotation::Rotate270 => height as u32 - x,
+        Rotation::Rotate0 | Rotation::Rotate180 => width - 1 - x,
+        Rotation::Rotate90 | Rotation::Rotate270 => height - 1 - x,
     };
 
     match rotation {
@@ -269,7 +380,7 @@ fn rotation(x: u32, y: u32, width: u32, height: u32, rotation: Rotation) -> (u32
         ),
         Rotation::Rotate270 => {
             let index = y / 8;
-            let height_offset = height - x;
+            let height_offset = height - 1 - x;
             let multiplier = width / 8;
             let additive = height_offset * multiplier;
             let index = index + additive;
@@ -282,19 +393,18 @@ fn rotation(x: u32, y: u32, width: u32, height: u32, rotation: Rotation) -> (u32
 }
 
 #[cfg(feature = "graphics")]
-impl<'a, I, SPI> DrawTarget for Display<'a, I, SPI>
+impl<'a, I> DrawTarget for Display<'a, I>
 where
-    SPI: embedded_hal::spi::SpiDevice,
-    I: DisplayInterface + DisplayCommands<SPI>,
+    I: DisplayInterface,
 {
     type Color = BinaryColor;
-    type Error = core::convert::Infallible;
+    type Error = <I as DisplayInterface>::Error;
 
     /// Draw pixels from an iterator onto the display.
     ///
     /// This method takes an iterator of [Pixel] items and sets the corresponding
     /// pixels in the display buffer. After drawing, it updates the display to
-    /// reflect the changes.
+    /// reflect the changes if `auto_update` is enabled in the [Config](crate::config::Config).
     ///
     /// # Arguments
     ///
@@ -302,8 +412,8 @@ where
     ///
     /// # Returns
     ///
-    /// * `Result<(), Self::Error>` - Always returns `Ok(())` since the error type is infallible.
-    ///   This indicates that the drawing operation cannot fail.
+    /// * `Result<(), Self::Error>` - Drawing into the buffer cannot fail, an error is only
+    ///   returned if the automatic update of the display fails.
     ///
     /// [Pixel]: https://docs.rs/embedded-graphics-core/0.4.0/embedded_graphics_core/struct.Pixel.html
     fn draw_iter<Iter>(&mut self, pixels: Iter) -> Result<(), Self::Error>
@@ -325,10 +435,9 @@ where
             }
         }
 
-        // Refresh the display, ignoring any errors if auto_update is enabled
+        // Refresh the display if auto_update is enabled
         if self.config.auto_update {
-            // TODO: Handle errors
-            let _ = self.update(DisplayUpdateMode::Fast);
+            self.update(DisplayUpdateMode::Fast)?;
         }
 
         Ok(())
@@ -336,10 +445,9 @@ where
 }
 
 #[cfg(feature = "graphics")]
-impl<'a, I, SPI> OriginDimensions for Display<'a, I, SPI>
+impl<'a, I> OriginDimensions for Display<'a, I>
 where
-    SPI: embedded_hal::spi::SpiDevice,
-    I: DisplayInterface + DisplayCommands<SPI>,
+    I: DisplayInterface,
 {
     /// Get the size of the display in pixels.
     ///
@@ -361,3 +469,65 @@ where
         }
     }
 }
+
+impl<'a, I> Image for Display<'a, I>
+where
+    I: DisplayInterface,
+{
+    /// Get the size of the buffer in pixels, taking the rotation into account.
+    fn image_size(&self) -> (u32, u32) {
+        logical_size(self.rows(), self.cols(), self.rotation())
+    }
+
+    /// The buffer is black and white
+    fn max_level(&self) -> u8 {
+        1
+    }
+
+    /// Get the level of a pixel in the buffer, 1 for white and 0 for black.
+    fn level(&self, x: u32, y: u32) -> u8 {
+        buffer_bit(
+            self.bw_buffer,
+            x,
+            y,
+            self.rows(),
+            self.cols(),
+            self.rotation(),
+        )
+        .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::rotation;
+    use crate::basic_display::Rotation;
+
+    #[test]
+    fn rotation_stays_within_buffer_at_edges() {
+        // 16 rows of 24 columns, the buffer holds 3 bytes per row
+        let (width, height) = (24, 16);
+
+        // The logical pixel, and the buffer index and bit it is expected at
+        let cases = [
+            (Rotation::Rotate0, (0, 0), (2, 0x01)),
+            (Rotation::Rotate0, (23, 15), (45, 0x80)),
+            (Rotation::Rotate90, (0, 0), (47, 0x01)),
+            (Rotation::Rotate90, (15, 23), (0, 0x80)),
+            (Rotation::Rotate180, (0, 0), (45, 0x80)),
+            (Rotation::Rotate180, (23, 0), (47, 0x01)),
+            (Rotation::Rotate180, (23, 15), (2, 0x01)),
+            (Rotation::Rotate270, (0, 0), (0, 0x80)),
+            (Rotation::Rotate270, (15, 0), (45, 0x80)),
+            (Rotation::Rotate270, (15, 23), (47, 0x01)),
+        ];
+
+        for (display_rotation, (x, y), expected) in cases {
+            assert_eq!(
+                rotation(x, y, width, height, display_rotation),
+                expected,
+                "{display_rotation:?} ({x}, {y})"
+            );
+        }
+    }
+}
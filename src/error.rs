@@ -1,17 +1,107 @@
 //! This module defines the error types for the SSD1677 display controller.
 //!
 //! The [SSD1677Error] enum encapsulates the various errors that can occur
-//! when interacting with the SSD1677 display. Currently, it includes:
+//! when interacting with the SSD1677 display. It is generic over the error type of the SPI
+//! device, the error type of the output pins and the error type of the busy pin, and includes:
 //!
-//! - [SetPinError](self::SSD1677Error::SetPinError): An error that occurs when there is a failure in setting
-//!   a pin, which may indicate issues with hardware connections or
+//! - [Spi](self::SSD1677Error::Spi): The SPI device returned an error while communicating with
+//!   the controller.
+//! - [Pin](self::SSD1677Error::Pin): An error that occurs when there is a failure in setting
+//!   or reading a pin, which may indicate issues with hardware connections or
 //!   configuration.
+//! - [Busy](self::SSD1677Error::Busy): An error that occurs when reading or waiting on the busy
+//!   pin fails.
+//! - [BusyTimeout](self::SSD1677Error::BusyTimeout): The controller kept the busy pin high for
+//!   longer than the configured timeout. This usually means the panel is not connected, or the
+//!   busy line is stuck.
+//! - [ReadUnsupported](self::SSD1677Error::ReadUnsupported): The interface can not read from
+//!   the controller, such as in 3-wire SPI mode.
+//! - [NoResetDelay](self::SSD1677Error::NoResetDelay): The controller could not be woken from
+//!   deep sleep, as the interface has no delay to time the hardware reset with.
+//!
+//! Updating a region of the display can also fail because of the region itself, which is
+//! reported with a [RegionError].
 //!
 //! This error handling mechanism allows users of the SSD1677 display driver
 //! to gracefully handle and respond to errors that may arise during
 //! operation.
+use core::fmt;
+
 #[derive(Debug)]
-pub enum SSD1677Error {
+pub enum SSD1677Error<SPI, PIN, BUSY = PIN> {
+    /// An error returned by the SPI device.
+    Spi(SPI),
     /// An error that occurs when there is a failure in setting a pin.
-    SetPinError,
+    Pin(PIN),
+    /// An error that occurs when there is a failure in reading the busy pin.
+    Busy(BUSY),
+    /// The controller did not become ready before the busy timeout expired.
+    BusyTimeout,
+    /// The interface can not read data from the controller.
+    ReadUnsupported,
+    /// The interface has no delay to time the hardware reset that wakes the controller.
+    NoResetDelay,
+}
+
+impl<SPI, PIN, BUSY> fmt::Display for SSD1677Error<SPI, PIN, BUSY>
+where
+    SPI: fmt::Debug,
+    PIN: fmt::Debug,
+    BUSY: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SSD1677Error::Spi(e) => write!(f, "SPI error: {:?}", e),
+            SSD1677Error::Pin(e) => write!(f, "GPIO pin error: {:?}", e),
+            SSD1677Error::Busy(e) => write!(f, "busy pin error: {:?}", e),
+            SSD1677Error::BusyTimeout => write!(f, "timed out waiting for the busy pin"),
+            SSD1677Error::ReadUnsupported => write!(f, "reading is not supported"),
+            SSD1677Error::NoResetDelay => write!(f, "no delay to time the reset with"),
+        }
+    }
+}
+
+impl<SPI, PIN, BUSY> core::error::Error for SSD1677Error<SPI, PIN, BUSY>
+where
+    SPI: fmt::Debug,
+    PIN: fmt::Debug,
+    BUSY: fmt::Debug,
+{
+}
+
+/// Error returned by [BasicDisplay::update_region](crate::basic_display::BasicDisplay::update_region).
+///
+/// It is generic over the error type of the
+/// [DisplayInterface](crate::interface::DisplayInterface) the display is updated through.
+#[derive(Debug)]
+pub enum RegionError<E> {
+    /// The region is empty, does not fit within the display, or is not aligned to whole bytes.
+    InvalidRegion,
+    /// A buffer does not hold exactly the bytes of the region.
+    InvalidBufferLength,
+    /// An error returned by the interface while updating the display.
+    Interface(E),
+}
+
+impl<E> From<E> for RegionError<E> {
+    fn from(error: E) -> Self {
+        RegionError::Interface(error)
+    }
+}
+
+impl<E> fmt::Display for RegionError<E>
+where
+    E: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegionError::InvalidRegion => write!(f, "region does not fit the display RAM"),
+            RegionError::InvalidBufferLength => {
+                write!(f, "buffer length does not match the region")
+            }
+            RegionError::Interface(e) => write!(f, "interface error: {:?}", e),
+        }
+    }
 }
+
+impl<E> core::error::Error for RegionError<E> where E: fmt::Debug {}
@@ -0,0 +1,235 @@
+//! This module provides export of display images to image files, for snapshot tests and bug
+//! reports.
+//!
+//! Anything that implements [Image] can be exported. The [Display](crate::display::Display), the
+//! [GrayscaleDisplay](crate::grayscale_display::GrayscaleDisplay) and, with the `simulator`
+//! feature, the RAM of the [SimulatedController](crate::simulator::SimulatedController) implement
+//! it. Images are exported in the logical orientation of the display, as drawn into with the
+//! rotation applied.
+//!
+//! The plain PBM and PGM formats are text, so they are written to any [core::fmt::Write] and are
+//! easy to compare in tests. With the `std` feature, images can also be written as PNG to any
+//! [std::io::Write].
+//!
+//! ```
+//! use ssd1677::export::{write_pbm, Image};
+//!
+//! /// A 2x2 checkerboard
+//! struct Checkerboard;
+//!
+//! impl Image for Checkerboard {
+//!     fn image_size(&self) -> (u32, u32) {
+//!         (2, 2)
+//!     }
+//!
+//!     fn max_level(&self) -> u8 {
+//!         1
+//!     }
+//!
+//!     fn level(&self, x: u32, y: u32) -> u8 {
+//!         ((x + y) % 2) as u8
+//!     }
+//! }
+//!
+//! let mut pbm = String::new();
+//! write_pbm(&Checkerboard, &mut pbm).unwrap();
+//! ```
+use core::fmt;
+
+use crate::basic_display::Rotation;
+use crate::display::rotation;
+
+/// An image that can be exported, made up of pixels with a level of gray.
+pub trait Image {
+    /// Returns the width and height of the image in pixels
+    fn image_size(&self) -> (u32, u32);
+
+    /// Returns the level of white of the lightest pixel, 1 for black and white images
+    fn max_level(&self) -> u8;
+
+    /// Returns the level of gray of a pixel, from 0 for black up to [Image::max_level] for white
+    fn level(&self, x: u32, y: u32) -> u8;
+}
+
+/// Write an image as a plain PBM, black pixels are any pixel with level 0.
+///
+/// # Arguments
+///
+/// * `image` - The image to write.
+/// * `out` - The writer to write the PBM to.
+pub fn write_pbm<W: fmt::Write>(image: &impl Image, out: &mut W) -> fmt::Result {
+    let (width, height) = image.image_size();
+    writeln!(out, "P1\n{} {}", width, height)?;
+
+    for y in 0..height {
+        for x in 0..width {
+            // A set bit is black in PBM, no separator is needed between the bits
+            let bit = u8::from(image.level(x, y) == 0);
+            write!(out, "{}", bit)?;
+        }
+        writeln!(out)?;
+    }
+
+    Ok(())
+}
+
+/// Write an image as a plain PGM, using the levels of gray of the image.
+///
+/// # Arguments
+///
+/// * `image` - The image to write.
+/// * `out` - The writer to write the PGM to.
+pub fn write_pgm<W: fmt::Write>(image: &impl Image, out: &mut W) -> fmt::Result {
+    let (width, height) = image.image_size();
+    writeln!(out, "P2\n{} {}\n{}", width, height, image.max_level())?;
+
+    for y in 0..height {
+        for x in 0..width {
+            let separator = if x == 0 { "" } else { " " };
+            write!(out, "{}{}", separator, image.level(x, y))?;
+        }
+        writeln!(out)?;
+    }
+
+    Ok(())
+}
+
+/// Write an image as a grayscale PNG.
+///
+/// The image is stored uncompressed, with the smallest bit depth that holds all levels of gray.
+///
+/// # Arguments
+///
+/// * `image` - The image to write.
+/// * `out` - The writer to write the PNG to.
+#[cfg(feature = "std")]
+pub fn write_png<W: std::io::Write>(image: &impl Image, out: &mut W) -> std::io::Result<()> {
+    use std::vec::Vec;
+
+    let (width, height) = image.image_size();
+    let max_level = u32::from(image.max_level().max(1));
+    let bit_depth: u8 = match max_level {
+        1 => 1,
+        2..=3 => 2,
+        4..=15 => 4,
+        _ => 8,
+    };
+    let max_sample = (1u32 << bit_depth) - 1;
+
+    // Pack the samples of every row, each row starts with filter type 0
+    let mut raw = Vec::new();
+    for y in 0..height {
+        raw.push(0);
+        let mut byte = 0u8;
+        let mut bits = 0;
+        for x in 0..width {
+            let sample = u32::from(image.level(x, y)).min(max_level) * max_sample / max_level;
+            byte = (byte << bit_depth) | sample as u8;
+            bits += bit_depth;
+            if bits == 8 {
+                raw.push(byte);
+                byte = 0;
+                bits = 0;
+            }
+        }
+        if bits > 0 {
+            raw.push(byte << (8 - bits));
+        }
+    }
+
+    // Wrap the rows in a zlib stream of stored deflate blocks
+    let mut zlib = Vec::with_capacity(raw.len() + raw.len() / 0xFFFF * 5 + 11);
+    zlib.extend_from_slice(&[0x78, 0x01]);
+    let mut blocks = raw.chunks(0xFFFF).peekable();
+    if blocks.peek().is_none() {
+        zlib.extend_from_slice(&[0x01, 0x00, 0x00, 0xFF, 0xFF]);
+    }
+    while let Some(block) = blocks.next() {
+        let len = block.len() as u16;
+        zlib.push(u8::from(blocks.peek().is_none()));
+        zlib.extend_from_slice(&len.to_le_bytes());
+        zlib.extend_from_slice(&(!len).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+    zlib.extend_from_slice(&adler32(&raw).to_be_bytes());
+
+    let mut header = [0u8; 13];
+    header[0..4].copy_from_slice(&width.to_be_bytes());
+    header[4..8].copy_from_slice(&height.to_be_bytes());
+    header[8] = bit_depth;
+    // Color type 0 is grayscale, followed by the default compression, filter and interlace
+    header[9..13].copy_from_slice(&[0, 0, 0, 0]);
+
+    out.write_all(&[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'])?;
+    write_png_chunk(out, b"IHDR", &header)?;
+    write_png_chunk(out, b"IDAT", &zlib)?;
+    write_png_chunk(out, b"IEND", &[])
+}
+
+/// Write a PNG chunk with its length and CRC
+#[cfg(feature = "std")]
+fn write_png_chunk<W: std::io::Write>(
+    out: &mut W,
+    kind: &[u8; 4],
+    data: &[u8],
+) -> std::io::Result<()> {
+    out.write_all(&(data.len() as u32).to_be_bytes())?;
+    out.write_all(kind)?;
+    out.write_all(data)?;
+
+    let crc = !crc32(crc32(0xFFFF_FFFF, kind), data);
+    out.write_all(&crc.to_be_bytes())
+}
+
+/// Update a CRC-32 as used by PNG with the given bytes
+#[cfg(feature = "std")]
+fn crc32(mut crc: u32, bytes: &[u8]) -> u32 {
+    for &byte in bytes {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = match crc & 1 {
+                0 => crc >> 1,
+                _ => (crc >> 1) ^ 0xEDB8_8320,
+            };
+        }
+    }
+
+    crc
+}
+
+/// Returns the Adler-32 checksum of the bytes, as used by zlib
+#[cfg(feature = "std")]
+fn adler32(bytes: &[u8]) -> u32 {
+    let (a, b) = bytes.iter().fold((1u32, 0u32), |(a, b), &byte| {
+        let a = (a + u32::from(byte)) % 65521;
+        (a, (b + a) % 65521)
+    });
+
+    (b << 16) | a
+}
+
+/// Returns the width and height of a display in the logical orientation
+pub(crate) fn logical_size(rows: u16, cols: u16, rotation: Rotation) -> (u32, u32) {
+    match rotation {
+        Rotation::Rotate0 | Rotation::Rotate180 => (cols.into(), rows.into()),
+        Rotation::Rotate90 | Rotation::Rotate270 => (rows.into(), cols.into()),
+    }
+}
+
+/// Returns the bit of a display buffer for a pixel in the logical orientation.
+///
+/// The pixel is located the same way as when drawing it, pixels outside of the buffer are unset.
+pub(crate) fn buffer_bit(
+    buffer: &[u8],
+    x: u32,
+    y: u32,
+    rows: u16,
+    cols: u16,
+    display_rotation: Rotation,
+) -> bool {
+    let (index, bit) = rotation(x, y, cols.into(), rows.into(), display_rotation);
+
+    buffer
+        .get(index as usize)
+        .is_some_and(|byte| byte & bit != 0)
+}
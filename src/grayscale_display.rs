@@ -0,0 +1,318 @@
+//! This module provides the [GrayscaleDisplay] struct for driving a black and white display
+//! with four levels of gray.
+//!
+//! The controller has no grayscale RAM, instead the two bits of every pixel are split over the
+//! black and white RAM and the red RAM. A waveform LUT made for grayscale then drives every
+//! combination of the two bits to a different level of gray. The LUT depends on the panel and
+//! must be supplied by the user, see [Lut].
+//!
+//! [GrayscaleDisplay] implements the [DrawTarget] trait from the [embedded-graphics-core] crate
+//! with [Gray2] as the color type, and is only available with the `graphics` feature.
+//!
+//! [DrawTarget]: https://docs.rs/embedded-graphics-core/0.4.0/embedded_graphics_core/draw_target/trait.DrawTarget.html
+//! [Gray2]: https://docs.rs/embedded-graphics-core/0.4.0/embedded_graphics_core/pixelcolor/struct.Gray2.html
+//! [embedded-graphics-core]: https://crates.io/crates/embedded-graphics-core
+use crate::basic_display::{BasicDisplay, DisplayUpdateMode, Rotation};
+use crate::config;
+use crate::display::rotation;
+use crate::export::{buffer_bit, logical_size, Image};
+use crate::interface::DisplayInterface;
+use crate::lut::Lut;
+use embedded_graphics_core::{pixelcolor::Gray2, prelude::*};
+
+#[cfg(feature = "defmt")]
+use defmt::*;
+
+/// A display that holds a 2 bits per pixel buffer for drawing in four levels of gray.
+///
+/// The buffer is split into two planes of one bit per pixel. The first half holds the high
+/// bit of every pixel and is written to the black and white RAM, the second half holds the low
+/// bit and is written to the red RAM. A set bit is lighter, so [Gray2::WHITE] sets both bits
+/// and [Gray2::BLACK] clears both. The grayscale [Lut] must map the transitions of the
+/// controller to the levels of gray accordingly.
+///
+/// [Gray2]: https://docs.rs/embedded-graphics-core/0.4.0/embedded_graphics_core/pixelcolor/struct.Gray2.html
+/// [Gray2::WHITE]: https://docs.rs/embedded-graphics-core/0.4.0/embedded_graphics_core/pixelcolor/struct.Gray2.html#associatedconstant.WHITE
+/// [Gray2::BLACK]: https://docs.rs/embedded-graphics-core/0.4.0/embedded_graphics_core/pixelcolor/struct.Gray2.html#associatedconstant.BLACK
+pub struct GrayscaleDisplay<'a, I>
+where
+    I: DisplayInterface,
+{
+    display: BasicDisplay<I>, // The underlying display interface
+    high_plane: &'a mut [u8], // The high bit of every pixel, for the black and white RAM
+    low_plane: &'a mut [u8],  // The low bit of every pixel, for the red RAM
+    lut: Lut,                 // The grayscale waveform LUT
+}
+
+impl<'a, I> GrayscaleDisplay<'a, I>
+where
+    I: DisplayInterface,
+{
+    /// Creates a new [GrayscaleDisplay] instance.
+    ///
+    /// This function creates a [BasicDisplay] using the provided interface and configuration,
+    /// then promotes it to a [GrayscaleDisplay] using the provided buffer and LUT.
+    ///
+    /// # Parameters
+    ///
+    /// - `interface`: An instance of the interface type `I` that will be used for communication
+    ///   with the display hardware, such as [Interface4Pin].
+    /// - `buffer`: A mutable reference to the buffer for the pixel data. This buffer must be
+    ///   `rows * cols / 4` bytes long, two bits for every pixel.
+    /// - `config`: An instance of [Config] that contains the configuration settings for
+    ///   the display.
+    /// - `lut`: The grayscale waveform LUT for the panel, see [Lut].
+    ///
+    /// # Returns
+    ///
+    /// Returns a new [GrayscaleDisplay] instance that is ready for use.
+    ///
+    /// [Interface4Pin]: crate::interface::Interface4Pin
+    /// [Config]: crate::config::Config
+    pub fn new(interface: I, buffer: &'a mut [u8], config: config::Config, lut: Lut) -> Self {
+        // First create a basic display
+        let d = BasicDisplay::new(interface, config);
+
+        // Promote the basic display to a GrayscaleDisplay
+        GrayscaleDisplay::from_basic_display(d, buffer, lut)
+    }
+
+    /// Promote a [BasicDisplay] to a [GrayscaleDisplay].
+    ///
+    /// The buffer should be of length `rows * cols / 4`, where `rows` and `cols` are the
+    /// dimensions of the display.
+    ///
+    /// # Arguments
+    ///
+    /// * `display` - The underlying display instance.
+    /// * `buffer` - A mutable reference to the buffer for the pixel data.
+    /// * `lut` - The grayscale waveform LUT for the panel.
+    pub fn from_basic_display(display: BasicDisplay<I>, buffer: &'a mut [u8], lut: Lut) -> Self {
+        // Split the buffer into the two planes
+        let plane_len = buffer.len() / 2;
+        let (high_plane, low_plane) = buffer.split_at_mut(plane_len);
+
+        GrayscaleDisplay {
+            display,
+            high_plane,
+            low_plane,
+            lut,
+        }
+    }
+
+    /// Update the display by writing both planes of the buffer to the controller.
+    ///
+    /// The grayscale LUT is loaded first if the controller is not using it already, so the
+    /// display can be shared with other users of the [BasicDisplay].
+    ///
+    /// # Arguments
+    ///
+    /// * `mode` - The kind of update to perform, see [DisplayUpdateMode] for details.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), <I as DisplayInterface>::Error>` - Returns `Ok(())` on success,
+    ///   or an error if the update fails.
+    pub fn update(
+        &mut self,
+        mode: DisplayUpdateMode,
+    ) -> Result<(), <I as DisplayInterface>::Error> {
+        if self.display.lut() != Some(&self.lut) {
+            self.display.load_lut(self.lut)?;
+        }
+
+        self.display
+            .update(Some(self.high_plane), Some(self.low_plane), mode)
+    }
+
+    /// Clear the buffer, filling it with a single level of gray.
+    ///
+    /// # Arguments
+    ///
+    /// * `color` - The level of gray to fill the buffer with, represented as a [Gray2].
+    ///
+    /// [Gray2]: https://docs.rs/embedded-graphics-core/0.4.0/embedded_graphics_core/pixelcolor/struct.Gray2.html
+    pub fn clear(&mut self, color: Gray2) -> Result<(), <I as DisplayInterface>::Error> {
+        // Spread each bit of the level over a whole byte of its plane
+        let fill = |bit: u8| match color.luma() & bit {
+            0 => 0x00,
+            _ => 0xFF,
+        };
+
+        self.high_plane.fill(fill(0b10));
+        self.low_plane.fill(fill(0b01));
+
+        // Refresh the display if auto_update is enabled
+        if self.display.config.auto_update {
+            self.update(DisplayUpdateMode::Slow)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Set a pixel at the specified coordinates to the given level of gray.
+    ///
+    /// This method updates both planes to reflect the color of the pixel at the
+    /// specified `(x, y)` coordinates, taking into account the current rotation
+    /// of the display.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - The x-coordinate of the pixel.
+    /// * `y` - The y-coordinate of the pixel.
+    /// * `color` - The level of gray to set the pixel to, represented as a [Gray2].
+    ///
+    /// [Gray2]: https://docs.rs/embedded-graphics-core/0.4.0/embedded_graphics_core/pixelcolor/struct.Gray2.html
+    pub fn set_pixel(&mut self, x: u32, y: u32, color: Gray2) {
+        #[cfg(feature = "defmt")]
+        trace!(
+            "Setting pixel on (x: {}, y: {}) to `{}` with rotation {}",
+            x,
+            y,
+            color,
+            self.rotation()
+        );
+
+        // Find out the buffer index and bit value, both planes share the same layout
+        let (index, bit) = rotation(
+            x,
+            y,
+            self.cols() as u32,
+            self.rows() as u32,
+            self.rotation(),
+        );
+        let index = index as usize;
+
+        #[cfg(feature = "defmt")]
+        trace!("Setting pixel on index {} to {}", index, bit);
+
+        // Set the value in both planes
+        for (plane, plane_bit) in [(&mut *self.high_plane, 0b10), (&mut *self.low_plane, 0b01)] {
+            match color.luma() & plane_bit {
+                0 => plane[index] &= !bit,
+                _ => plane[index] |= bit,
+            }
+        }
+    }
+}
+
+impl<'a, I> core::ops::Deref for GrayscaleDisplay<'a, I>
+where
+    I: DisplayInterface,
+{
+    type Target = BasicDisplay<I>;
+
+    /// Dereference to access the underlying [BasicDisplay] instance.
+    fn deref(&self) -> &BasicDisplay<I> {
+        &self.display
+    }
+}
+
+impl<'a, I> core::ops::DerefMut for GrayscaleDisplay<'a, I>
+where
+    I: DisplayInterface,
+{
+    /// Mutably dereference to access the underlying [BasicDisplay] instance.
+    fn deref_mut(&mut self) -> &mut BasicDisplay<I> {
+        &mut self.display
+    }
+}
+
+impl<'a, I> DrawTarget for GrayscaleDisplay<'a, I>
+where
+    I: DisplayInterface,
+{
+    type Color = Gray2;
+    type Error = <I as DisplayInterface>::Error;
+
+    /// Draw pixels from an iterator onto the display.
+    ///
+    /// This method takes an iterator of [Pixel] items and sets the corresponding
+    /// pixels in the buffer. After drawing, it updates the display to
+    /// reflect the changes if `auto_update` is enabled in the [Config](crate::config::Config).
+    ///
+    /// # Arguments
+    ///
+    /// * `pixels` - An iterator of [`Pixel<Self::Color>`][Pixel] items to draw on the display.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), Self::Error>` - Drawing into the buffer cannot fail, an error is only
+    ///   returned if the automatic update of the display fails.
+    ///
+    /// [Pixel]: https://docs.rs/embedded-graphics-core/0.4.0/embedded_graphics_core/struct.Pixel.html
+    fn draw_iter<Iter>(&mut self, pixels: Iter) -> Result<(), Self::Error>
+    where
+        Iter: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let size = self.size();
+
+        #[cfg(feature = "defmt")]
+        trace!("Drawing to the display");
+
+        // Draw the image pixel by pixel
+        for Pixel(Point { x, y }, color) in pixels {
+            let x = x as u32;
+            let y = y as u32;
+
+            if x < size.width && y < size.height {
+                self.set_pixel(x, y, color);
+            }
+        }
+
+        // Refresh the display if auto_update is enabled
+        if self.config.auto_update {
+            self.update(DisplayUpdateMode::Slow)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a, I> OriginDimensions for GrayscaleDisplay<'a, I>
+where
+    I: DisplayInterface,
+{
+    /// Get the size of the display in pixels, taking the rotation into account.
+    fn size(&self) -> Size {
+        match self.rotation() {
+            Rotation::Rotate0 | Rotation::Rotate180 => {
+                Size::new(self.cols().into(), self.rows().into())
+            }
+            Rotation::Rotate90 | Rotation::Rotate270 => {
+                Size::new(self.rows().into(), self.cols().into())
+            }
+        }
+    }
+}
+
+impl<'a, I> Image for GrayscaleDisplay<'a, I>
+where
+    I: DisplayInterface,
+{
+    /// Get the size of the buffer in pixels, taking the rotation into account.
+    fn image_size(&self) -> (u32, u32) {
+        logical_size(self.rows(), self.cols(), self.rotation())
+    }
+
+    /// The buffer holds four levels of gray
+    fn max_level(&self) -> u8 {
+        3
+    }
+
+    /// Get the level of gray of a pixel in the buffer, combining the bits of both planes.
+    fn level(&self, x: u32, y: u32) -> u8 {
+        let bit = |plane: &[u8]| {
+            u8::from(buffer_bit(
+                plane,
+                x,
+                y,
+                self.rows(),
+                self.cols(),
+                self.rotation(),
+            ))
+        };
+
+        (bit(self.high_plane) << 1) | bit(self.low_plane)
+    }
+}
@@ -2,12 +2,29 @@
 //!
 //! This module implements the functions required to communicate and interface
 //! with the SSD1677 display controller. It provides a trait for display
-//! communication and a specific implementation for 4-pin SPI mode.
+//! communication and implementations for the 4-wire SPI mode ([Interface4Pin]) and the
+//! 3-wire SPI mode ([Interface3Pin]) of the controller.
+use crate::command::{Command, MAX_PARAMETERS_LEN};
+use crate::error::SSD1677Error;
 use embedded_hal;
+use embedded_hal::spi::Operation;
 
 /// 10ms reset delay as seen in box 2 in chapter 9.1 in the SSD1677 datasheet
 pub const RESET_DELAY_MS: u8 = 10;
 
+/// Interval between polls of the busy pin when waiting with a [DelayTimeout]
+pub const BUSY_POLL_INTERVAL_US: u32 = 100;
+
+/// Default maximum number of bytes per SPI transfer of [Interface4Pin].
+///
+/// Linux has a default limit of 4096 bytes per SPI transfer, other targets are not limited.
+/// <https://github.com/torvalds/linux/blob/ccda4af0f4b92f7b4c308d3acc262f4a7e3affad/drivers/spi/spidev.c#L93>
+pub const DEFAULT_MAX_TRANSFER_LEN: usize = if cfg!(target_os = "linux") {
+    4096
+} else {
+    usize::MAX
+};
+
 /// Trait implemented by displays for core functionality
 ///
 /// This trait defines the essential methods required for communication with
@@ -18,7 +35,7 @@ pub const RESET_DELAY_MS: u8 = 10;
 ///
 /// * `Error` - The type of error that can occur during communication.
 pub trait DisplayInterface {
-    type Error;
+    type Error: core::fmt::Debug;
 
     /// Send a command to the display controller
     fn send_command(&mut self, command: u8) -> Result<(), Self::Error>;
@@ -26,14 +43,234 @@ pub trait DisplayInterface {
     /// Send data for a command
     fn send_data(&mut self, data: &[u8]) -> Result<(), Self::Error>;
 
+    /// Send data for a command without waiting for the controller afterwards.
+    ///
+    /// This is needed for commands after which the controller keeps the busy pin high, such
+    /// as entering deep sleep. The default calls [DisplayInterface::send_data], interfaces that
+    /// wait for the controller after sending data must override it.
+    fn send_data_without_busy_wait(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        self.send_data(data)
+    }
+
+    /// Send a command and read the data the controller returns for it.
+    ///
+    /// The controller first returns `dummy.len()` dummy bytes, which are read into `dummy`,
+    /// followed by the data. The controller returns the data on its bidirectional SDA pin, so
+    /// reading requires SDA to be wired to MISO as well as MOSI.
+    ///
+    /// Interfaces that can not read from the controller must return an error, such as
+    /// [SSD1677Error::ReadUnsupported], so a missing controller is not mistaken for one
+    /// returning zeroes.
+    fn read_command(
+        &mut self,
+        command: u8,
+        dummy: &mut [u8],
+        data: &mut [u8],
+    ) -> Result<(), Self::Error>;
+
     /// Reset the controller
-    fn reset<D: embedded_hal::delay::DelayNs>(&mut self, delay: &mut D);
+    fn reset<D: embedded_hal::delay::DelayNs>(&mut self, delay: &mut D) -> Result<(), Self::Error>;
+
+    /// Wake the controller from deep sleep.
+    ///
+    /// The controller can only leave deep sleep through a hardware reset. Unlike
+    /// [DisplayInterface::reset] no delay is supplied, so the interface must time the reset
+    /// with a delay of its own, see [Interface4Pin::with_reset_delay].
+    ///
+    /// Interfaces that can not reset the controller must return an error, such as
+    /// [SSD1677Error::NoResetDelay], so updates are not sent to a sleeping controller.
+    fn wake(&mut self) -> Result<(), Self::Error>;
 
     /// Wait for the controller to indicate that it is not busy.
     ///
     /// This method blocks until the display controller is ready to accept new commands
     /// or data, ensuring that operations are synchronized with the display's state.
-    fn busy_wait(&mut self);
+    ///
+    /// An error is returned if the busy state can not be read, or if the controller stays
+    /// busy for longer than the interface allows.
+    fn busy_wait(&mut self) -> Result<(), Self::Error>;
+
+    /// Send a [Command] with its parameters to the controller.
+    ///
+    /// Waits for the controller after the commands that keep it busy, and does not wait after
+    /// entering deep sleep, the same as the methods of
+    /// [DisplayCommands](crate::command::DisplayCommands).
+    fn execute(&mut self, command: Command<'_>) -> Result<(), Self::Error> {
+        let mut buf = [0u8; MAX_PARAMETERS_LEN];
+        let (opcode, parameters) = command.encode(&mut buf);
+
+        self.send_command(opcode)?;
+        match command {
+            // Do not wait for the busy pin, it will not go low again while sleeping
+            Command::DeepSleepMode(_) => self.send_data_without_busy_wait(parameters)?,
+            _ if !parameters.is_empty() => self.send_data(parameters)?,
+            _ => {}
+        }
+        if let Command::SoftwareReset | Command::UpdateDisplay = command {
+            self.busy_wait()?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Timer bounding how long [DisplayInterface::busy_wait] may block.
+///
+/// The timer is started every time the interface begins waiting on the busy pin, and is then
+/// checked between every poll of the pin until the controller is ready.
+///
+/// The crate provides the following timers:
+/// * [NoTimeout] never expires, the interface waits forever. This is the default.
+/// * [DelayTimeout] counts elapsed time using a [DelayNs](embedded_hal::delay::DelayNs).
+/// * [ClockTimeout] reads elapsed time from a caller-provided clock.
+///
+/// Other time sources can be used by implementing this trait.
+pub trait BusyTimer {
+    /// Start timing a new wait
+    fn start(&mut self);
+
+    /// Returns `true` once the wait started by [BusyTimer::start] has timed out
+    fn expired(&mut self) -> bool;
+}
+
+/// A [BusyTimer] that never expires.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoTimeout;
+
+impl BusyTimer for NoTimeout {
+    fn start(&mut self) {}
+
+    fn expired(&mut self) -> bool {
+        false
+    }
+}
+
+/// A [BusyTimer] driven by a [DelayNs](embedded_hal::delay::DelayNs).
+///
+/// Every check of the timer delays for [BUSY_POLL_INTERVAL_US] and adds it to the elapsed
+/// time, so the time spent reading the busy pin itself is not accounted for.
+pub struct DelayTimeout<D> {
+    delay: D,
+    timeout_us: u32,
+    elapsed_us: u32,
+}
+
+impl<D> DelayTimeout<D>
+where
+    D: embedded_hal::delay::DelayNs,
+{
+    /// Create a new `DelayTimeout`.
+    ///
+    /// # Arguments
+    ///
+    /// * `delay` - The delay used to wait between polls of the busy pin.
+    /// * `timeout_ms` - How long to wait for the controller before timing out, in milliseconds.
+    pub fn new(delay: D, timeout_ms: u32) -> Self {
+        Self {
+            delay,
+            timeout_us: timeout_ms.saturating_mul(1000),
+            elapsed_us: 0,
+        }
+    }
+}
+
+impl<D> BusyTimer for DelayTimeout<D>
+where
+    D: embedded_hal::delay::DelayNs,
+{
+    fn start(&mut self) {
+        self.elapsed_us = 0;
+    }
+
+    fn expired(&mut self) -> bool {
+        if self.elapsed_us >= self.timeout_us {
+            return true;
+        }
+
+        self.delay.delay_us(BUSY_POLL_INTERVAL_US);
+        self.elapsed_us = self.elapsed_us.saturating_add(BUSY_POLL_INTERVAL_US);
+
+        false
+    }
+}
+
+/// A [BusyTimer] driven by a caller-provided clock.
+///
+/// The clock is a closure returning the current time in milliseconds, such as
+/// `|| Instant::now().as_millis() as u32` on embassy. The clock is allowed to wrap around.
+pub struct ClockTimeout<C> {
+    clock: C,
+    timeout_ms: u32,
+    start_ms: u32,
+}
+
+impl<C> ClockTimeout<C>
+where
+    C: FnMut() -> u32,
+{
+    /// Create a new `ClockTimeout`.
+    ///
+    /// # Arguments
+    ///
+    /// * `clock` - Closure returning the current time in milliseconds.
+    /// * `timeout_ms` - How long to wait for the controller before timing out, in milliseconds.
+    pub fn new(clock: C, timeout_ms: u32) -> Self {
+        Self {
+            clock,
+            timeout_ms,
+            start_ms: 0,
+        }
+    }
+}
+
+impl<C> BusyTimer for ClockTimeout<C>
+where
+    C: FnMut() -> u32,
+{
+    fn start(&mut self) {
+        self.start_ms = (self.clock)();
+    }
+
+    fn expired(&mut self) -> bool {
+        (self.clock)().wrapping_sub(self.start_ms) >= self.timeout_ms
+    }
+}
+
+/// Delay stored in an interface to time the hardware reset of [DisplayInterface::wake].
+///
+/// Implemented for every [DelayNs](embedded_hal::delay::DelayNs), and by [NoResetDelay] for
+/// interfaces without a delay of their own.
+pub trait ResetDelay {
+    /// Returns true if the delay can time a reset
+    fn can_reset(&self) -> bool;
+
+    /// Wait for `ms` milliseconds
+    fn wait_ms(&mut self, ms: u32);
+}
+
+/// A [ResetDelay] for interfaces without a delay, which can not wake the controller.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoResetDelay;
+
+impl ResetDelay for NoResetDelay {
+    fn can_reset(&self) -> bool {
+        false
+    }
+
+    fn wait_ms(&mut self, _ms: u32) {}
+}
+
+impl<D> ResetDelay for D
+where
+    D: embedded_hal::delay::DelayNs,
+{
+    fn can_reset(&self) -> bool {
+        true
+    }
+
+    fn wait_ms(&mut self, ms: u32) {
+        self.delay_ms(ms);
+    }
 }
 
 /// Interface to the SSD1677 driver operating in 4pin SPI mode
@@ -42,7 +279,21 @@ pub trait DisplayInterface {
 /// the SSD1677 display controller using a 4-pin SPI interface. It includes
 /// methods for sending commands and data, as well as handling the reset and
 /// busy states of the display.
-pub struct Interface4Pin<SPI, OUT, IN> {
+///
+/// All methods report failures as an [SSD1677Error], with errors of the busy pin reported
+/// separately from those of the output pins.
+///
+/// Reads need the SDA pin of the controller wired to MISO as well as MOSI, see
+/// [DisplayInterface::read_command].
+///
+/// By default the interface waits forever for the controller to finish a command, use
+/// [Interface4Pin::with_busy_timeout] to bound the wait. To wake the controller from deep sleep
+/// the interface needs a delay, set with [Interface4Pin::with_reset_delay].
+///
+/// Large writes, such as the image data, are split into transfers of at most
+/// [DEFAULT_MAX_TRANSFER_LEN] bytes, use [Interface4Pin::with_max_transfer_len] to match the
+/// limit of the SPI driver.
+pub struct Interface4Pin<SPI, OUT, IN, T = NoTimeout, R = NoResetDelay> {
     /// The SpiDevice to communicate with the display
     spi: SPI,
     /// Data / Command pin, 0=command, 1=data
@@ -51,9 +302,16 @@ pub struct Interface4Pin<SPI, OUT, IN> {
     pub reset_pin: OUT,
     /// The pin from the controller indicating busy
     busy_pin: IN,
+    /// The timer bounding how long to wait on the busy pin
+    busy_timer: T,
+    /// The delay timing the reset when waking the controller
+    reset_delay: R,
+    /// The maximum number of bytes per SPI transfer
+    max_transfer_len: usize,
+    /// Whether the data/command pin is only set once for all transfers of a write
+    hold_data_command: bool,
 }
 
-// Implement the interface functions
 impl<SPI, OUT, IN> Interface4Pin<SPI, OUT, IN>
 where
     SPI: embedded_hal::spi::SpiDevice,
@@ -81,31 +339,135 @@ where
             data_command_pin,
             reset_pin,
             busy_pin,
+            busy_timer: NoTimeout,
+            reset_delay: NoResetDelay,
+            max_transfer_len: DEFAULT_MAX_TRANSFER_LEN,
+            hold_data_command: true,
+        }
+    }
+}
+
+// Implement the interface functions
+impl<SPI, OUT, IN, T, R> Interface4Pin<SPI, OUT, IN, T, R>
+where
+    SPI: embedded_hal::spi::SpiDevice,
+    OUT: embedded_hal::digital::OutputPin,
+    IN: embedded_hal::digital::InputPin,
+    T: BusyTimer,
+    R: ResetDelay,
+{
+    /// Bound how long the interface waits for the controller to be ready.
+    ///
+    /// The timer applies to every wait on the busy pin, including [busy_wait],
+    /// [refresh_display] and [reset_software]. A refresh can take a few seconds, so the timeout
+    /// should be chosen with that in mind. When the timer expires the wait fails with
+    /// [SSD1677Error::BusyTimeout].
+    ///
+    /// # Arguments
+    ///
+    /// * `busy_timer` - The timer to use, such as a [DelayTimeout] or a [ClockTimeout].
+    ///
+    /// [busy_wait]: DisplayInterface::busy_wait
+    /// [refresh_display]: crate::command::DisplayCommands::refresh_display
+    /// [reset_software]: crate::command::DisplayCommands::reset_software
+    pub fn with_busy_timeout<T2: BusyTimer>(
+        self,
+        busy_timer: T2,
+    ) -> Interface4Pin<SPI, OUT, IN, T2, R> {
+        Interface4Pin {
+            spi: self.spi,
+            data_command_pin: self.data_command_pin,
+            reset_pin: self.reset_pin,
+            busy_pin: self.busy_pin,
+            busy_timer,
+            reset_delay: self.reset_delay,
+            max_transfer_len: self.max_transfer_len,
+            hold_data_command: self.hold_data_command,
         }
     }
 
+    /// Set the delay used to time the hardware reset that wakes the controller from deep sleep.
+    ///
+    /// [DisplayInterface::reset] is given a delay by the caller, but waking happens on the next
+    /// update after [sleep], so the interface needs a delay of its own. Without one
+    /// [DisplayInterface::wake] fails with [SSD1677Error::NoResetDelay].
+    ///
+    /// # Arguments
+    ///
+    /// * `reset_delay` - The delay to use, any [DelayNs](embedded_hal::delay::DelayNs).
+    ///
+    /// [sleep]: crate::basic_display::BasicDisplay::sleep
+    pub fn with_reset_delay<R2: ResetDelay>(
+        self,
+        reset_delay: R2,
+    ) -> Interface4Pin<SPI, OUT, IN, T, R2> {
+        Interface4Pin {
+            spi: self.spi,
+            data_command_pin: self.data_command_pin,
+            reset_pin: self.reset_pin,
+            busy_pin: self.busy_pin,
+            busy_timer: self.busy_timer,
+            reset_delay,
+            max_transfer_len: self.max_transfer_len,
+            hold_data_command: self.hold_data_command,
+        }
+    }
+
+    /// Set the maximum number of bytes sent in a single SPI transfer.
+    ///
+    /// Writes larger than this are split into multiple transfers. The default is
+    /// [DEFAULT_MAX_TRANSFER_LEN], pass `usize::MAX` to never split a write, for example when
+    /// the `bufsiz` of a Linux spidev has been raised.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_transfer_len` - The maximum transfer length in bytes, a length of 0 is treated as 1.
+    pub fn with_max_transfer_len(mut self, max_transfer_len: usize) -> Self {
+        self.max_transfer_len = max_transfer_len.max(1);
+        self
+    }
+
+    /// Set whether the data/command pin is held across the transfers of a split write.
+    ///
+    /// By default the pin is set once before the first transfer and held for the rest of the
+    /// write. When the pin can be changed by others between transfers, such as a data/command
+    /// line shared with other devices on the bus, pass `false` to set it again before every
+    /// transfer.
+    ///
+    /// # Arguments
+    ///
+    /// * `hold` - Whether to hold the data/command pin for all transfers of a write.
+    pub fn with_data_command_held(mut self, hold: bool) -> Self {
+        self.hold_data_command = hold;
+        self
+    }
+
     /// Write data over SPI.
     ///
-    /// This method sends a byte array of data to the display over the SPI interface.
-    /// It handles chunking the data if the target operating system has a limit on
-    /// the size of SPI transfers.
+    /// This method sets the data/command pin and sends a byte array of data to the display over
+    /// the SPI interface. The data is split into transfers of at most the maximum transfer length.
     ///
     /// # Arguments
     ///
+    /// * `is_data` - The level of the data/command pin, `true` for data.
     /// * `data` - A slice of bytes representing the data to send.
     ///
     /// # Returns
     ///
-    /// * `Result<(), SPI::Error>` - Returns `Ok(())` on success, or an error if the write operation fails.
-    fn write(&mut self, data: &[u8]) -> Result<(), SPI::Error> {
-        // Linux has a default limit of 4096 bytes per SPI transfer
-        // https://github.com/torvalds/linux/blob/ccda4af0f4b92f7b4c308d3acc262f4a7e3affad/drivers/spi/spidev.c#L93
-        if cfg!(target_os = "linux") {
-            for data_chunk in data.chunks(4096) {
-                self.spi.write(data_chunk)?;
+    /// * `Result<(), <Self as DisplayInterface>::Error>` - Returns `Ok(())` on success, or an
+    ///   error if setting the pin or the write operation fails.
+    fn write(
+        &mut self,
+        is_data: bool,
+        data: &[u8],
+    ) -> Result<(), <Self as DisplayInterface>::Error> {
+        for (index, data_chunk) in data.chunks(self.max_transfer_len).enumerate() {
+            if index == 0 || !self.hold_data_command {
+                self.data_command_pin
+                    .set_state(is_data.into())
+                    .map_err(SSD1677Error::Pin)?;
             }
-        } else {
-            self.spi.write(data)?;
+            self.spi.write(data_chunk).map_err(SSD1677Error::Spi)?;
         }
 
         Ok(())
@@ -113,51 +475,397 @@ where
 }
 
 /// Implement the DisplayInterface functions
-impl<SPI, OUT, IN> DisplayInterface for Interface4Pin<SPI, OUT, IN>
+impl<SPI, OUT, IN, T, R> DisplayInterface for Interface4Pin<SPI, OUT, IN, T, R>
 where
     SPI: embedded_hal::spi::SpiDevice,
     OUT: embedded_hal::digital::OutputPin,
     IN: embedded_hal::digital::InputPin,
+    T: BusyTimer,
+    R: ResetDelay,
 {
-    type Error = SPI::Error;
+    type Error = SSD1677Error<SPI::Error, OUT::Error, IN::Error>;
 
-    fn reset<D: embedded_hal::delay::DelayNs>(&mut self, delay: &mut D) {
-        // Disable the display, the wait for the controller to catch up
-        self.reset_pin.set_low().unwrap();
+    fn reset<D: embedded_hal::delay::DelayNs>(&mut self, delay: &mut D) -> Result<(), Self::Error> {
+        // Disable the display, then wait for the controller to catch up
+        self.reset_pin.set_low().map_err(SSD1677Error::Pin)?;
         delay.delay_ms(RESET_DELAY_MS.into());
-        // Enable the display, the wait for the controller to catch up
-        self.reset_pin.set_high().unwrap();
+        // Enable the display, then wait for the controller to catch up
+        self.reset_pin.set_high().map_err(SSD1677Error::Pin)?;
         delay.delay_ms(RESET_DELAY_MS.into());
+
+        Ok(())
     }
 
     fn send_command(&mut self, command: u8) -> Result<(), Self::Error> {
-        // Set the data/command pin as low to indicate command
-        self.data_command_pin.set_low().unwrap();
-        // Send tthe data
-        self.write(&[command])?;
+        // Send the command with the data/command pin low
+        self.write(false, &[command])?;
 
         // Wait for the device to be ready
-        self.busy_wait();
+        self.busy_wait()?;
 
         Ok(())
     }
 
     fn send_data(&mut self, data: &[u8]) -> Result<(), Self::Error> {
-        // Set the data/command pin as high to indicate data
-        self.data_command_pin.set_high().unwrap();
-        // Send the data
-        self.write(data)?;
+        // Send the data with the data/command pin high
+        self.write(true, data)?;
 
         // Wait for the device to be ready
-        self.busy_wait();
+        self.busy_wait()?;
+
+        Ok(())
+    }
+
+    fn send_data_without_busy_wait(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        // Send the data with the data/command pin high
+        self.write(true, data)?;
+
+        Ok(())
+    }
+
+    fn read_command(
+        &mut self,
+        command: u8,
+        dummy: &mut [u8],
+        data: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        // Send the command with the data/command pin low
+        self.write(false, &[command])?;
+
+        // The controller clocks out the data while the data/command pin is high. The pin can not
+        // change mid-transaction, so the dummy bytes and the data are read in a transaction of
+        // their own, the same as data following a command when writing.
+        self.data_command_pin
+            .set_high()
+            .map_err(SSD1677Error::Pin)?;
+        self.spi
+            .transaction(&mut [Operation::Read(dummy), Operation::Read(data)])
+            .map_err(SSD1677Error::Spi)?;
 
         Ok(())
     }
 
-    fn busy_wait(&mut self) {
-        while match self.busy_pin.is_high() {
-            Ok(x) => x,
-            _ => false,
-        } {}
+    fn wake(&mut self) -> Result<(), Self::Error> {
+        // Leave the reset pin alone if the reset can not be timed
+        if !self.reset_delay.can_reset() {
+            return Err(SSD1677Error::NoResetDelay);
+        }
+
+        // Disable the display, then wait for the controller to catch up
+        self.reset_pin.set_low().map_err(SSD1677Error::Pin)?;
+        self.reset_delay.wait_ms(RESET_DELAY_MS.into());
+        // Enable the display, then wait for the controller to catch up
+        self.reset_pin.set_high().map_err(SSD1677Error::Pin)?;
+        self.reset_delay.wait_ms(RESET_DELAY_MS.into());
+
+        Ok(())
+    }
+
+    fn busy_wait(&mut self) -> Result<(), Self::Error> {
+        self.busy_timer.start();
+
+        while self.busy_pin.is_high().map_err(SSD1677Error::Busy)? {
+            if self.busy_timer.expired() {
+                return Err(SSD1677Error::BusyTimeout);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Number of 9-bit frames that fill a whole number of bytes
+const FRAMES_PER_BLOCK: usize = 8;
+
+/// Number of bytes holding [FRAMES_PER_BLOCK] frames
+const BLOCK_LEN: usize = 9;
+
+/// Number of blocks packed and written to the SPI device at a time
+const BLOCKS_PER_WRITE: usize = 32;
+
+/// The hardware interface for the 3-wire SPI mode of the controller.
+///
+/// In 3-wire mode there is no data/command pin. Instead every byte is sent as a 9-bit frame,
+/// with the data/command bit in front of the eight data bits. The frames are packed into bytes
+/// for a standard 8-bit [SpiDevice](embedded_hal::spi::SpiDevice), so the SPI bus does not need
+/// to support 9-bit words. When the number of frames is not a multiple of eight the last byte is
+/// padded with zero bits, which the controller discards when chip select is released.
+///
+/// Reading from the controller is not supported in 3-wire mode, as the padding of the command
+/// frame can not be sent in the same transaction as the read. Reads fail with
+/// [SSD1677Error::ReadUnsupported].
+pub struct Interface3Pin<SPI, OUT, IN, T = NoTimeout, R = NoResetDelay> {
+    /// The SpiDevice to communicate with the display
+    spi: SPI,
+    /// The reset pin for the display
+    pub reset_pin: OUT,
+    /// The pin from the controller indicating busy
+    busy_pin: IN,
+    /// The timer bounding how long to wait on the busy pin
+    busy_timer: T,
+    /// The delay timing the reset when waking the controller
+    reset_delay: R,
+}
+
+impl<SPI, OUT, IN> Interface3Pin<SPI, OUT, IN>
+where
+    SPI: embedded_hal::spi::SpiDevice,
+    OUT: embedded_hal::digital::OutputPin,
+    IN: embedded_hal::digital::InputPin,
+{
+    /// Create a new `Interface3Pin`.
+    ///
+    /// This constructor initializes a new instance of the `Interface3Pin` struct
+    /// with the provided SPI device and pin configurations.
+    ///
+    /// # Arguments
+    ///
+    /// * `spi` - The SPI device used for communication with the display.
+    /// * `reset_pin` - The pin used to reset the display.
+    /// * `busy_pin` - The pin used to check if the display is busy.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - A new instance of `Interface3Pin`.
+    pub fn new(spi: SPI, reset_pin: OUT, busy_pin: IN) -> Self {
+        Self {
+            spi,
+            reset_pin,
+            busy_pin,
+            busy_timer: NoTimeout,
+            reset_delay: NoResetDelay,
+        }
+    }
+}
+
+impl<SPI, OUT, IN, T, R> Interface3Pin<SPI, OUT, IN, T, R>
+where
+    SPI: embedded_hal::spi::SpiDevice,
+    OUT: embedded_hal::digital::OutputPin,
+    IN: embedded_hal::digital::InputPin,
+    T: BusyTimer,
+    R: ResetDelay,
+{
+    /// Bound how long the interface waits for the controller to be ready.
+    ///
+    /// See [Interface4Pin::with_busy_timeout].
+    ///
+    /// # Arguments
+    ///
+    /// * `busy_timer` - The timer to use, such as a [DelayTimeout] or a [ClockTimeout].
+    pub fn with_busy_timeout<T2: BusyTimer>(
+        self,
+        busy_timer: T2,
+    ) -> Interface3Pin<SPI, OUT, IN, T2, R> {
+        Interface3Pin {
+            spi: self.spi,
+            reset_pin: self.reset_pin,
+            busy_pin: self.busy_pin,
+            busy_timer,
+            reset_delay: self.reset_delay,
+        }
+    }
+
+    /// Set the delay used to time the hardware reset that wakes the controller from deep sleep.
+    ///
+    /// See [Interface4Pin::with_reset_delay].
+    ///
+    /// # Arguments
+    ///
+    /// * `reset_delay` - The delay to use, any [DelayNs](embedded_hal::delay::DelayNs).
+    pub fn with_reset_delay<R2: ResetDelay>(
+        self,
+        reset_delay: R2,
+    ) -> Interface3Pin<SPI, OUT, IN, T, R2> {
+        Interface3Pin {
+            spi: self.spi,
+            reset_pin: self.reset_pin,
+            busy_pin: self.busy_pin,
+            busy_timer: self.busy_timer,
+            reset_delay,
+        }
+    }
+
+    /// Write bytes over SPI as 9-bit frames.
+    ///
+    /// The frames are packed a few blocks at a time, so every write stays below the transfer
+    /// limit of Linux and only whole blocks are split between writes.
+    ///
+    /// # Arguments
+    ///
+    /// * `is_data` - The data/command bit of every frame, `true` for data.
+    /// * `data` - A slice of bytes representing the data to send.
+    fn write_frames(&mut self, is_data: bool, data: &[u8]) -> Result<(), SPI::Error> {
+        let mut packed = [0u8; BLOCK_LEN * BLOCKS_PER_WRITE];
+
+        for data_chunk in data.chunks(FRAMES_PER_BLOCK * BLOCKS_PER_WRITE) {
+            let len = pack_frames(is_data, data_chunk, &mut packed);
+            self.spi.write(&packed[..len])?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Pack bytes into 9-bit frames, most significant bit first.
+///
+/// Returns the number of bytes of `packed` that were filled. `packed` must be able to hold
+/// `data.len() * 9 / 8` bytes, rounded up.
+fn pack_frames(is_data: bool, data: &[u8], packed: &mut [u8]) -> usize {
+    let data_command_bit = u16::from(is_data) << 8;
+
+    // Bits that did not fill a whole byte yet, aligned to the right
+    let mut pending: u32 = 0;
+    let mut pending_bits = 0;
+    let mut len = 0;
+
+    for &byte in data {
+        pending = (pending << 9) | u32::from(data_command_bit | u16::from(byte));
+        pending_bits += 9;
+
+        while pending_bits >= 8 {
+            pending_bits -= 8;
+            packed[len] = (pending >> pending_bits) as u8;
+            len += 1;
+        }
+        pending &= (1 << pending_bits) - 1;
+    }
+
+    // Pad the last frame to a whole byte
+    if pending_bits > 0 {
+        packed[len] = (pending << (8 - pending_bits)) as u8;
+        len += 1;
+    }
+
+    len
+}
+
+/// Implement the DisplayInterface functions
+impl<SPI, OUT, IN, T, R> DisplayInterface for Interface3Pin<SPI, OUT, IN, T, R>
+where
+    SPI: embedded_hal::spi::SpiDevice,
+    OUT: embedded_hal::digital::OutputPin,
+    IN: embedded_hal::digital::InputPin,
+    T: BusyTimer,
+    R: ResetDelay,
+{
+    type Error = SSD1677Error<SPI::Error, OUT::Error, IN::Error>;
+
+    fn reset<D: embedded_hal::delay::DelayNs>(&mut self, delay: &mut D) -> Result<(), Self::Error> {
+        // Disable the display, then wait for the controller to catch up
+        self.reset_pin.set_low().map_err(SSD1677Error::Pin)?;
+        delay.delay_ms(RESET_DELAY_MS.into());
+        // Enable the display, then wait for the controller to catch up
+        self.reset_pin.set_high().map_err(SSD1677Error::Pin)?;
+        delay.delay_ms(RESET_DELAY_MS.into());
+
+        Ok(())
+    }
+
+    fn send_command(&mut self, command: u8) -> Result<(), Self::Error> {
+        // Send the command with the data/command bit low
+        self.write_frames(false, &[command])
+            .map_err(SSD1677Error::Spi)?;
+
+        // Wait for the device to be ready
+        self.busy_wait()?;
+
+        Ok(())
+    }
+
+    fn send_data(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        // Send the data with the data/command bit high
+        self.write_frames(true, data).map_err(SSD1677Error::Spi)?;
+
+        // Wait for the device to be ready
+        self.busy_wait()?;
+
+        Ok(())
+    }
+
+    fn send_data_without_busy_wait(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        // Send the data with the data/command bit high
+        self.write_frames(true, data).map_err(SSD1677Error::Spi)?;
+
+        Ok(())
+    }
+
+    fn read_command(
+        &mut self,
+        _command: u8,
+        _dummy: &mut [u8],
+        _data: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        // The padding after the 9-bit command frame would shift the data read back by 7 bits
+        Err(SSD1677Error::ReadUnsupported)
+    }
+
+    fn wake(&mut self) -> Result<(), Self::Error> {
+        // Leave the reset pin alone if the reset can not be timed
+        if !self.reset_delay.can_reset() {
+            return Err(SSD1677Error::NoResetDelay);
+        }
+
+        // Disable the display, then wait for the controller to catch up
+        self.reset_pin.set_low().map_err(SSD1677Error::Pin)?;
+        self.reset_delay.wait_ms(RESET_DELAY_MS.into());
+        // Enable the display, then wait for the controller to catch up
+        self.reset_pin.set_high().map_err(SSD1677Error::Pin)?;
+        self.reset_delay.wait_ms(RESET_DELAY_MS.into());
+
+        Ok(())
+    }
+
+    fn busy_wait(&mut self) -> Result<(), Self::Error> {
+        self.busy_timer.start();
+
+        while self.busy_pin.is_high().map_err(SSD1677Error::Busy)? {
+            if self.busy_timer.expired() {
+                return Err(SSD1677Error::BusyTimeout);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trace::{unpack_frames, Frame};
+
+    /// Pack bytes into frames, then unpack them again
+    fn round_trip(is_data: bool, data: &[u8]) {
+        let mut packed = [0u8; BLOCK_LEN * BLOCKS_PER_WRITE];
+        let len = pack_frames(is_data, data, &mut packed);
+        assert_eq!(len, (data.len() * 9).div_ceil(8));
+
+        let frames = data.iter().map(|&byte| Frame { is_data, byte });
+        assert!(unpack_frames(&packed[..len]).eq(frames));
+    }
+
+    #[test]
+    fn frames_round_trip() {
+        let mut data = [0u8; 3 * FRAMES_PER_BLOCK];
+        for (index, byte) in data.iter_mut().enumerate() {
+            *byte = (index as u8).wrapping_mul(73) ^ 0xC3;
+        }
+
+        // Every length over a few blocks, so each amount of padding at the end is covered
+        for len in 0..=data.len() {
+            round_trip(false, &data[..len]);
+            round_trip(true, &data[..len]);
+        }
+    }
+
+    #[test]
+    fn frames_are_packed_msb_first() {
+        let mut packed = [0u8; 3];
+
+        assert_eq!(pack_frames(false, &[0xFF], &mut packed), 2);
+        assert_eq!(packed[..2], [0x7F, 0x80]);
+
+        assert_eq!(pack_frames(true, &[0x00, 0xFF], &mut packed), 3);
+        assert_eq!(packed, [0x80, 0x7F, 0xC0]);
     }
 }
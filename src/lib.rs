@@ -16,27 +16,53 @@
 //!
 //! ### Creation and initialization
 //!
-//! The [`Interface`] has the hardware connection details to the SSD1677 controller. This crate only
+//! The [`Interface`] has the hardware connection details to the SSD1677 controller. This crate
 //! implements the 4-wire SPI communication, for this a SPI device and some GPIO pins are required.
+//! Boards wired for the 3-wire SPI mode, without a data/command pin, can use
+//! [`Interface3Pin`](interface::Interface3Pin) instead.
 //!
 //! To configure the details of your specific display create a [`Config`]. This contains information
 //! about the size of the display, and the display rotation.  
 //! To construct the Config use the [Builder] interface.
 //!
 //! The SSD1677 controller can control many different displays of varying sizes and color
-//! capabilities. This driver should work on any size display the controller can do, the underlying
-//! functions for supporting red color are implemented in the driver, but [`Display`]
-//! used to support [embedded-graphics] does not implement it at the current time.
+//! capabilities. This driver should work on any size display the controller can do. For black and
+//! white panels use [`Display`], for black, white and red panels use [`TriColorDisplay`], which
+//! draws [`TriColor`] pixels into a second buffer for the red plane. Black and white panels can
+//! also show four levels of gray with [`GrayscaleDisplay`], given a grayscale waveform [`Lut`] for
+//! the panel.
 //!
-//! Lastly create a [`Display`] with the [`Config`].
+//! Lastly create a [`Display`] or [`TriColorDisplay`] with the [`Config`].
 //! The display must be reset before use.
 //!
+//! ### Async
+//!
+//! With the `async` feature flag enabled the `asynch` module provides async versions of the
+//! interface, [`BasicDisplay`] and [`Display`], built on [embedded-hal-async]. These yield to the
+//! executor while the controller is busy instead of blocking it during a refresh.
+//!
+//! ### Export
+//!
+//! The contents of a display buffer can be exported as a PBM or PGM image with the `export`
+//! module, or as a PNG with the `std` feature flag enabled, to snapshot test drawing code.
+//!
+//! ### Simulator
+//!
+//! With the `simulator` feature flag enabled the `simulator` module provides an in-memory model of
+//! the controller, which can be used in place of the interface to test drawing code without a
+//! panel.
+//!
+//! ### Tracing
+//!
+//! The `trace` module wraps any interface to record every command sent to the controller, and
+//! decodes captured bus traffic back into named commands with their arguments.
+//!
 //!
 //! #### Example
 //! The following example is a snippet from the example in the `examples` folder of the repository.
 //! This snippet of the example does not compile on it's own, but demonstrates a basic implementation of the display driver.
 //!
-//! ```rust
+//! ```rust,ignore
 //! use ssd1677::{self, interface::Interface4Pin};
 //!
 //! fn main() -> ! {
@@ -78,15 +104,36 @@
 //! [`Interface`]: interface/struct.Interface4Pin.html
 //! [`BasicDisplay`]: basic_display/struct.BasicDisplay.html
 //! [embedded-graphics]: https://crates.io/crates/embedded-graphics
+//! [embedded-hal-async]: https://crates.io/crates/embedded-hal-async
 //! [Builder]: confg/struct.Builder.html
 
+#[cfg(feature = "simulator")]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+#[cfg(feature = "async")]
+pub mod asynch;
 pub mod basic_display;
 pub mod command;
 pub mod config;
 pub mod display;
 pub mod error;
+pub mod export;
+#[cfg(feature = "graphics")]
+pub mod grayscale_display;
 pub mod interface;
+pub mod lut;
+#[cfg(feature = "simulator")]
+pub mod simulator;
+pub mod trace;
+pub mod tri_color_display;
+pub mod voltage;
 
-pub use basic_display::{Dimensions, Rotation};
+pub use basic_display::{Dimensions, Region, Rotation};
 pub use config::{Builder as ConfigBuilder, Config};
 pub use display::Display;
+#[cfg(feature = "graphics")]
+pub use grayscale_display::GrayscaleDisplay;
+pub use lut::Lut;
+pub use tri_color_display::{TriColor, TriColorDisplay};
@@ -0,0 +1,205 @@
+//! This module provides the [Lut] struct describing a waveform look-up table for the
+//! SSD1677 controller.
+//!
+//! The waveform LUT tells the controller which voltages to drive the source outputs with,
+//! and for how long, while refreshing the display. The controller normally loads a LUT from
+//! its OTP memory, but a custom LUT can be written with
+//! [DisplayCommands::write_lut](crate::command::DisplayCommands::write_lut) to tune the
+//! refresh for a specific panel.
+//!
+//! A LUT consists of [LUT_GROUPS] groups, which are run one after another. Every group has
+//! [LUT_PHASES] phases, during which each of the [LUT_TRANSITIONS] transitions drives its pixels
+//! with a [SourceLevel]. The transition used for a pixel is selected by the values of the pixel
+//! in the black and white, and the red RAM.
+
+/// The number of groups in a LUT
+pub const LUT_GROUPS: usize = 10;
+/// The number of phases in every group of a LUT
+pub const LUT_PHASES: usize = 4;
+/// The number of transitions in a LUT, each driving the pixels with a different waveform
+pub const LUT_TRANSITIONS: usize = 5;
+/// The number of bytes sent to the controller for a LUT
+pub const LUT_LEN: usize = 105;
+
+/// The voltage a source output is driven with during a phase.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum SourceLevel {
+    /// Ground, the pixel is not driven
+    #[default]
+    VSS = 0b00,
+    /// The first positive source voltage
+    VSH1 = 0b01,
+    /// The negative source voltage
+    VSL = 0b10,
+    /// The second positive source voltage
+    VSH2 = 0b11,
+}
+
+impl SourceLevel {
+    /// Convert the two bit value used by the controller into a [SourceLevel]
+    fn from_bits(bits: u8) -> Self {
+        match bits & 0b11 {
+            0b00 => SourceLevel::VSS,
+            0b01 => SourceLevel::VSH1,
+            0b10 => SourceLevel::VSL,
+            _ => SourceLevel::VSH2,
+        }
+    }
+}
+
+/// A single group of a [Lut].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LutGroup {
+    /// The levels of every transition during each of the phases A to D of the group
+    pub levels: [[SourceLevel; LUT_PHASES]; LUT_TRANSITIONS],
+    /// The length of each of the phases A to D in frames, a length of 0 skips the phase
+    pub phase_frames: [u8; LUT_PHASES],
+    /// The number of times the group is repeated, 0 runs the group once
+    pub repeat: u8,
+    /// The frame rate setting of the group, only the lower four bits are used
+    pub frame_rate: u8,
+}
+
+/// A waveform look-up table that can be written to the controller in place of the OTP LUT.
+///
+/// The LUT can be built up group by group, or created from the bytes of a LUT provided by the
+/// panel manufacturer with [Lut::from_bytes].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Lut {
+    /// The groups of the LUT, in the order they are run
+    pub groups: [LutGroup; LUT_GROUPS],
+}
+
+impl Lut {
+    // Offsets of the sections of the encoded LUT
+    const PHASE_FRAMES_OFFSET: usize = LUT_TRANSITIONS * LUT_GROUPS;
+    const REPEAT_OFFSET: usize = Self::PHASE_FRAMES_OFFSET + LUT_GROUPS * LUT_PHASES;
+    const FRAME_RATE_OFFSET: usize = Self::REPEAT_OFFSET + LUT_GROUPS;
+
+    /// Create a LUT from the bytes sent to the controller with command 0x32.
+    ///
+    /// The bytes are laid out as in the datasheet: the source levels of every transition and
+    /// group, followed by the phase lengths and repeat counts of every group, and lastly the
+    /// frame rates packed two groups to a byte.
+    pub fn from_bytes(bytes: &[u8; LUT_LEN]) -> Self {
+        let mut lut = Lut::default();
+
+        for (index, group) in lut.groups.iter_mut().enumerate() {
+            // Every byte holds the levels of the four phases of one transition
+            for (transition, levels) in group.levels.iter_mut().enumerate() {
+                let byte = bytes[transition * LUT_GROUPS + index];
+                for (phase, level) in levels.iter_mut().enumerate() {
+                    *level = SourceLevel::from_bits(byte >> (6 - 2 * phase));
+                }
+            }
+
+            let phase_frames = Self::PHASE_FRAMES_OFFSET + index * LUT_PHASES;
+            group
+                .phase_frames
+                .copy_from_slice(&bytes[phase_frames..phase_frames + LUT_PHASES]);
+            group.repeat = bytes[Self::REPEAT_OFFSET + index];
+
+            // The first group of a pair is in the upper four bits
+            let frame_rate = bytes[Self::FRAME_RATE_OFFSET + index / 2];
+            group.frame_rate = match index % 2 {
+                0 => frame_rate >> 4,
+                _ => frame_rate & 0x0F,
+            };
+        }
+
+        lut
+    }
+
+    /// Encode the LUT into the bytes sent to the controller with command 0x32.
+    ///
+    /// This is the inverse of [Lut::from_bytes].
+    pub fn to_bytes(&self) -> [u8; LUT_LEN] {
+        let mut bytes = [0u8; LUT_LEN];
+
+        for (index, group) in self.groups.iter().enumerate() {
+            for (transition, levels) in group.levels.iter().enumerate() {
+                bytes[transition * LUT_GROUPS + index] =
+                    levels.iter().enumerate().fold(0, |byte, (phase, level)| {
+                        byte | (*level as u8) << (6 - 2 * phase)
+                    });
+            }
+
+            let phase_frames = Self::PHASE_FRAMES_OFFSET + index * LUT_PHASES;
+            bytes[phase_frames..phase_frames + LUT_PHASES].copy_from_slice(&group.phase_frames);
+            bytes[Self::REPEAT_OFFSET + index] = group.repeat;
+
+            let frame_rate = group.frame_rate & 0x0F;
+            bytes[Self::FRAME_RATE_OFFSET + index / 2] |= match index % 2 {
+                0 => frame_rate << 4,
+                _ => frame_rate,
+            };
+        }
+
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bytes_round_trip() {
+        // Every bit of the encoded LUT is used, so any bytes decode and encode unchanged
+        let mut bytes = [0u8; LUT_LEN];
+        for (index, byte) in bytes.iter_mut().enumerate() {
+            *byte = (index as u8).wrapping_mul(37) ^ 0x5A;
+        }
+
+        assert_eq!(Lut::from_bytes(&bytes).to_bytes(), bytes);
+        assert_eq!(
+            Lut::from_bytes(&[0xFF; LUT_LEN]).to_bytes(),
+            [0xFF; LUT_LEN]
+        );
+    }
+
+    #[test]
+    fn lut_round_trips() {
+        let mut lut = Lut::default();
+        lut.groups[0].levels[0] = [
+            SourceLevel::VSH1,
+            SourceLevel::VSL,
+            SourceLevel::VSH2,
+            SourceLevel::VSS,
+        ];
+        lut.groups[0].phase_frames = [1, 2, 3, 4];
+        lut.groups[0].repeat = 5;
+        lut.groups[0].frame_rate = 0x0A;
+        lut.groups[9].levels[4] = [SourceLevel::VSH2; LUT_PHASES];
+        lut.groups[9].phase_frames = [0xFF; LUT_PHASES];
+        lut.groups[9].repeat = 0xFF;
+        lut.groups[9].frame_rate = 0x0F;
+
+        assert_eq!(Lut::from_bytes(&lut.to_bytes()), lut);
+    }
+
+    #[test]
+    fn bytes_follow_datasheet_layout() {
+        let mut lut = Lut::default();
+        lut.groups[1].levels[2] = [
+            SourceLevel::VSH1,
+            SourceLevel::VSS,
+            SourceLevel::VSS,
+            SourceLevel::VSH2,
+        ];
+        lut.groups[1].phase_frames = [7, 0, 0, 9];
+        lut.groups[1].repeat = 3;
+        lut.groups[1].frame_rate = 0x04;
+
+        let bytes = lut.to_bytes();
+        assert_eq!(bytes[2 * LUT_GROUPS + 1], 0b01_00_00_11);
+        assert_eq!(bytes[50 + LUT_PHASES..50 + 2 * LUT_PHASES], [7, 0, 0, 9]);
+        assert_eq!(bytes[90 + 1], 3);
+        // The second group of a pair is in the lower four bits
+        assert_eq!(bytes[100], 0x04);
+    }
+}
@@ -0,0 +1,999 @@
+//! This module provides the [SimulatedController], an in-memory model of the SSD1677 controller
+//! for testing without a panel.
+//!
+//! The simulated controller implements [DisplayInterface], and decodes the commands and data it
+//! is sent like the controller would. It keeps the black and white, and the red RAM, and honours
+//! the data entry mode, the RAM window and the address counters when writing or reading RAM. When
+//! the display update sequence is activated, the contents of the RAM are copied to the simulated
+//! panel, so tests can check what the display would show after a refresh.
+//!
+//! The model covers the commands that affect the RAM and the refresh, other commands are accepted
+//! and ignored. The controller is never busy, and any delays are skipped.
+//!
+//! The simulated controller is only available with the `simulator` feature, and needs an
+//! allocator for the RAM.
+//!
+//! ```
+//! use ssd1677::basic_display::{BasicDisplay, DisplayUpdateMode};
+//! use ssd1677::command::RamPlane;
+//! use ssd1677::simulator::SimulatedController;
+//! use ssd1677::{ConfigBuilder, Dimensions};
+//!
+//! let config = ConfigBuilder::new()
+//!     .dimensions(Dimensions { rows: 16, cols: 16 })
+//!     .build()
+//!     .unwrap();
+//! let mut display = BasicDisplay::new(SimulatedController::new(), config);
+//! display.init().unwrap();
+//!
+//! // Draw a black pixel in the top left corner
+//! let mut buffer = [0xFF; 16 * 16 / 8];
+//! buffer[0] = 0x7F;
+//! display.update(Some(&buffer), None, DisplayUpdateMode::Slow).unwrap();
+//!
+//! let controller = display.interface_mut();
+//! let top_left_is_white = controller.displayed_pixel(RamPlane::BlackAndWhite, 0, 0);
+//! assert!(!top_left_is_white);
+//! ```
+use alloc::vec;
+use alloc::vec::Vec;
+use core::convert::Infallible;
+
+use crate::basic_display::{Dimensions, Rotation, MAX_GATE_OUTPUTS, MAX_SOURCE_OUTPUTS};
+use crate::command::{Celsius, DeepSleepMode, RamPlane};
+use crate::display::rotation;
+use crate::export::{logical_size, Image};
+use crate::interface::DisplayInterface;
+use crate::lut::{Lut, LUT_LEN};
+
+/// The number of bytes in a row of a RAM plane, every byte holds eight pixels
+pub const RAM_ROW_LEN: usize = MAX_SOURCE_OUTPUTS as usize / 8;
+
+/// The number of bytes in a RAM plane
+pub const RAM_LEN: usize = RAM_ROW_LEN * MAX_GATE_OUTPUTS as usize;
+
+/// The data entry mode after a reset, incrementing X then Y
+const POR_DATA_ENTRY_MODE: u8 = 0b011;
+
+/// The display update sequence after a reset
+const POR_UPDATE_SEQUENCE: u8 = 0xFF;
+
+/// An in-memory model of the SSD1677 controller.
+///
+/// The RAM planes hold [RAM_ROW_LEN] bytes per row, with the most significant bit of every byte
+/// holding the leftmost pixel. The X address counts pixels and the Y address counts rows, so a
+/// display buffer written from address 0 lands in the top left corner of the RAM.
+pub struct SimulatedController {
+    /// The black and white RAM
+    bw_ram: Vec<u8>,
+    /// The red RAM
+    red_ram: Vec<u8>,
+    /// The black and white RAM as shown by the last refresh
+    bw_displayed: Vec<u8>,
+    /// The red RAM as shown by the last refresh
+    red_displayed: Vec<u8>,
+    /// The last command received, which the following data belongs to
+    command: Option<u8>,
+    /// The parameters received for the last command
+    parameters: [u8; LUT_LEN],
+    /// The number of bytes received or read for the last command
+    data_len: usize,
+    /// The data entry mode, bit 0 increments X, bit 1 increments Y and bit 2 moves along Y first
+    data_entry_mode: u8,
+    /// The first and last X address of the RAM window
+    x_window: (u16, u16),
+    /// The first and last Y address of the RAM window
+    y_window: (u16, u16),
+    /// The X address counter
+    x_count: u16,
+    /// The Y address counter
+    y_count: u16,
+    /// The RAM read by the read RAM command
+    read_plane: RamPlane,
+    /// The number of gate lines driven
+    gate_lines: u16,
+    /// The RAM content options, the red option in the upper four bits
+    ram_options: u8,
+    /// The display update sequence run on activation
+    update_sequence: u8,
+    /// The custom waveform LUT, if written
+    lut: Option<Lut>,
+    /// The temperature register
+    temperature: Celsius,
+    /// The deep sleep mode
+    sleep_mode: DeepSleepMode,
+    /// The number of refreshes that displayed the RAM
+    refresh_count: usize,
+}
+
+impl SimulatedController {
+    /// Create a simulated controller with cleared RAM, as after power on.
+    pub fn new() -> Self {
+        let mut controller = SimulatedController {
+            bw_ram: vec![0; RAM_LEN],
+            red_ram: vec![0; RAM_LEN],
+            bw_displayed: vec![0; RAM_LEN],
+            red_displayed: vec![0; RAM_LEN],
+            command: None,
+            parameters: [0; LUT_LEN],
+            data_len: 0,
+            data_entry_mode: POR_DATA_ENTRY_MODE,
+            x_window: (0, 0),
+            y_window: (0, 0),
+            x_count: 0,
+            y_count: 0,
+            read_plane: RamPlane::BlackAndWhite,
+            gate_lines: 0,
+            ram_options: 0,
+            update_sequence: POR_UPDATE_SEQUENCE,
+            lut: None,
+            temperature: Celsius::from_degrees(25),
+            sleep_mode: DeepSleepMode::Normal,
+            refresh_count: 0,
+        };
+        controller.reset_registers();
+
+        controller
+    }
+
+    /// Returns a RAM plane, see [SimulatedController] for the layout
+    pub fn ram(&self, plane: RamPlane) -> &[u8] {
+        match plane {
+            RamPlane::BlackAndWhite => &self.bw_ram,
+            RamPlane::Red => &self.red_ram,
+        }
+    }
+
+    /// Returns the value of a pixel in a RAM plane, or `false` outside of the RAM
+    pub fn ram_pixel(&self, plane: RamPlane, x: u16, y: u16) -> bool {
+        pixel(self.ram(plane), x, y)
+    }
+
+    /// Returns a RAM plane as shown by the last refresh, after applying the RAM content options
+    pub fn displayed(&self, plane: RamPlane) -> &[u8] {
+        match plane {
+            RamPlane::BlackAndWhite => &self.bw_displayed,
+            RamPlane::Red => &self.red_displayed,
+        }
+    }
+
+    /// Returns the value of a pixel as shown by the last refresh, or `false` outside of the RAM.
+    ///
+    /// A set pixel in the black and white plane is white, a set pixel in the red plane is red on
+    /// a tri-color panel.
+    pub fn displayed_pixel(&self, plane: RamPlane, x: u16, y: u16) -> bool {
+        pixel(self.displayed(plane), x, y)
+    }
+
+    /// Returns a RAM plane as an [Image] in the logical orientation of a display.
+    ///
+    /// # Arguments
+    ///
+    /// * `plane` - The RAM plane to export, set pixels are exported as white.
+    /// * `dimensions` - The dimensions of the display the RAM was written by.
+    /// * `rotation` - The rotation of the display the RAM was written by.
+    pub fn ram_image(
+        &self,
+        plane: RamPlane,
+        dimensions: &Dimensions,
+        rotation: Rotation,
+    ) -> RamImage<'_> {
+        RamImage::new(self.ram(plane), dimensions, rotation)
+    }
+
+    /// Returns a RAM plane as shown by the last refresh, as an [Image] in the logical orientation
+    /// of a display.
+    ///
+    /// See [SimulatedController::ram_image] for the arguments.
+    pub fn displayed_image(
+        &self,
+        plane: RamPlane,
+        dimensions: &Dimensions,
+        rotation: Rotation,
+    ) -> RamImage<'_> {
+        RamImage::new(self.displayed(plane), dimensions, rotation)
+    }
+
+    /// Returns the number of refreshes that displayed the RAM
+    pub fn refresh_count(&self) -> usize {
+        self.refresh_count
+    }
+
+    /// Returns the X and Y address counters
+    pub fn address_counters(&self) -> (u16, u16) {
+        (self.x_count, self.y_count)
+    }
+
+    /// Returns the first and last X address, and the first and last Y address of the RAM window
+    pub fn ram_window(&self) -> ((u16, u16), (u16, u16)) {
+        (self.x_window, self.y_window)
+    }
+
+    /// Returns the number of gate lines driven, as set by the driver output control
+    pub fn gate_lines(&self) -> u16 {
+        self.gate_lines
+    }
+
+    /// Returns the custom waveform LUT, if one was written since the last reset
+    pub fn lut(&self) -> Option<&Lut> {
+        self.lut.as_ref()
+    }
+
+    /// Returns the temperature register
+    pub fn temperature(&self) -> Celsius {
+        self.temperature
+    }
+
+    /// Set the temperature register, as if measured by the temperature sensor
+    pub fn set_temperature(&mut self, temperature: Celsius) {
+        self.temperature = temperature;
+    }
+
+    /// Returns true if the controller is in deep sleep
+    pub fn is_asleep(&self) -> bool {
+        self.sleep_mode != DeepSleepMode::Normal
+    }
+
+    /// Reset the registers to their values after a reset, the RAM is kept
+    fn reset_registers(&mut self) {
+        self.command = None;
+        self.data_len = 0;
+        self.data_entry_mode = POR_DATA_ENTRY_MODE;
+        self.x_window = (0, MAX_SOURCE_OUTPUTS - 1);
+        self.y_window = (0, MAX_GATE_OUTPUTS - 1);
+        self.x_count = 0;
+        self.y_count = 0;
+        self.read_plane = RamPlane::BlackAndWhite;
+        self.gate_lines = MAX_GATE_OUTPUTS;
+        self.ram_options = 0;
+        self.update_sequence = POR_UPDATE_SEQUENCE;
+        self.lut = None;
+    }
+
+    /// Handle a hardware reset, which also leaves deep sleep
+    fn reset_hardware(&mut self) {
+        self.sleep_mode = DeepSleepMode::Normal;
+        self.reset_registers();
+    }
+
+    /// Handle a command byte
+    fn command(&mut self, command: u8) {
+        if self.is_asleep() {
+            return;
+        }
+
+        self.command = Some(command);
+        self.data_len = 0;
+
+        match command {
+            // Software reset
+            0x12 => self.reset_registers(),
+            // Master activation
+            0x20 => self.activate(),
+            _ => {}
+        }
+    }
+
+    /// Handle a data byte for the last command
+    fn data(&mut self, byte: u8) {
+        if self.is_asleep() {
+            return;
+        }
+        let Some(command) = self.command else {
+            return;
+        };
+
+        match command {
+            // Write RAM
+            0x24 => self.write_ram(RamPlane::BlackAndWhite, byte),
+            0x26 => self.write_ram(RamPlane::Red, byte),
+            _ => {
+                if let Some(parameter) = self.parameters.get_mut(self.data_len) {
+                    *parameter = byte;
+                }
+                self.data_len += 1;
+
+                if self.data_len == parameter_len(command) {
+                    self.apply_parameters(command);
+                }
+            }
+        }
+    }
+
+    /// Apply the parameters of a command once all have been received
+    fn apply_parameters(&mut self, command: u8) {
+        let p = self.parameters;
+        let address = |lower: u8, upper: u8| u16::from_le_bytes([lower, upper & 0b11]);
+
+        match command {
+            // Driver output control
+            0x01 => self.gate_lines = address(p[0], p[1]) + 1,
+            // Deep sleep mode
+            0x10 => self.deep_sleep(p[0] & 0b11),
+            // Data entry mode setting
+            0x11 => self.data_entry_mode = p[0] & 0b111,
+            // Write to temperature register
+            0x1A => self.temperature = Celsius::from_register([p[0], p[1]]),
+            // Display update control 1
+            0x21 => self.ram_options = p[0],
+            // Display update control 2
+            0x22 => self.update_sequence = p[0],
+            // Write LUT register
+            0x32 => {
+                let mut bytes = [0u8; LUT_LEN];
+                bytes.copy_from_slice(&p[..LUT_LEN]);
+                self.lut = Some(Lut::from_bytes(&bytes));
+            }
+            // Read RAM option
+            0x41 => {
+                self.read_plane = match p[0] & 0b1 {
+                    0 => RamPlane::BlackAndWhite,
+                    _ => RamPlane::Red,
+                }
+            }
+            // Set RAM X and Y address start/end position
+            0x44 => self.x_window = (address(p[0], p[1]), address(p[2], p[3])),
+            0x45 => self.y_window = (address(p[0], p[1]), address(p[2], p[3])),
+            // Auto write regular pattern
+            0x46 => fill_pattern(&mut self.red_ram, p[0]),
+            0x47 => fill_pattern(&mut self.bw_ram, p[0]),
+            // Set RAM X and Y address counter
+            0x4E => self.x_count = address(p[0], p[1]),
+            0x4F => self.y_count = address(p[0], p[1]),
+            _ => {}
+        }
+    }
+
+    /// Enter deep sleep, the RAM is lost when it is not preserved
+    fn deep_sleep(&mut self, mode: u8) {
+        self.sleep_mode = match mode {
+            0b00 => DeepSleepMode::Normal,
+            0b01 => DeepSleepMode::PreserveRAM,
+            _ => DeepSleepMode::DiscardRAM,
+        };
+
+        if self.sleep_mode == DeepSleepMode::DiscardRAM {
+            self.bw_ram.fill(0);
+            self.red_ram.fill(0);
+        }
+    }
+
+    /// Run the display update sequence, showing the RAM if the sequence includes the display step
+    fn activate(&mut self) {
+        if self.update_sequence & 0x04 == 0 {
+            return;
+        }
+
+        show(
+            &self.bw_ram,
+            &mut self.bw_displayed,
+            self.ram_options & 0x0F,
+        );
+        show(
+            &self.red_ram,
+            &mut self.red_displayed,
+            self.ram_options >> 4,
+        );
+        self.refresh_count += 1;
+    }
+
+    /// Write a byte to RAM at the address counters, then advance the counters
+    fn write_ram(&mut self, plane: RamPlane, byte: u8) {
+        if let Some(index) = self.ram_index() {
+            match plane {
+                RamPlane::BlackAndWhite => self.bw_ram[index] = byte,
+                RamPlane::Red => self.red_ram[index] = byte,
+            }
+        }
+
+        self.advance_counters();
+    }
+
+    /// Read a byte from RAM at the address counters, then advance the counters
+    fn read_ram(&mut self) -> u8 {
+        let byte = self
+            .ram_index()
+            .map_or(0, |index| self.ram(self.read_plane)[index]);
+        self.advance_counters();
+
+        byte
+    }
+
+    /// Returns the RAM index of the byte at the address counters, or `None` outside of the RAM
+    fn ram_index(&self) -> Option<usize> {
+        if self.x_count >= MAX_SOURCE_OUTPUTS || self.y_count >= MAX_GATE_OUTPUTS {
+            return None;
+        }
+
+        Some(self.y_count as usize * RAM_ROW_LEN + self.x_count as usize / 8)
+    }
+
+    /// Advance the address counters according to the data entry mode.
+    ///
+    /// The counter along the primary axis wraps around within the RAM window, moving the other
+    /// counter to the next row or column.
+    fn advance_counters(&mut self) {
+        let increment_x = self.data_entry_mode & 0b001 != 0;
+        let increment_y = self.data_entry_mode & 0b010 != 0;
+
+        if self.data_entry_mode & 0b100 == 0 {
+            if self.step_x(increment_x) {
+                self.step_y(increment_y);
+            }
+        } else if self.step_y(increment_y) {
+            self.step_x(increment_x);
+        }
+    }
+
+    /// Move the X address counter by one byte, returns true if it wrapped around the window
+    fn step_x(&mut self, increment: bool) -> bool {
+        // Every byte holds eight pixels, so the counter moves in whole bytes
+        let start = self.x_window.0 & !0b111;
+        let end = self.x_window.1 & !0b111;
+        let x = self.x_count & !0b111;
+
+        let (x, wrapped) = step(x, start, end, 8, increment);
+        self.x_count = x;
+
+        wrapped
+    }
+
+    /// Move the Y address counter by one row, returns true if it wrapped around the window
+    fn step_y(&mut self, increment: bool) -> bool {
+        let (y, wrapped) = step(self.y_count, self.y_window.0, self.y_window.1, 1, increment);
+        self.y_count = y;
+
+        wrapped
+    }
+
+    /// Fill a buffer with the data returned for the last command
+    fn read(&mut self, data: &mut [u8]) {
+        let command = match self.command {
+            Some(command) if !self.is_asleep() => command,
+            _ => {
+                data.fill(0);
+                return;
+            }
+        };
+
+        for byte in data.iter_mut() {
+            *byte = match command {
+                // Read RAM, the first byte is a dummy byte
+                0x27 if self.data_len == 0 => 0,
+                0x27 => self.read_ram(),
+                // Read temperature register
+                0x1B => self
+                    .temperature
+                    .to_register()
+                    .get(self.data_len)
+                    .copied()
+                    .unwrap_or(0),
+                // The status, OTP and user ID contents are not modelled
+                _ => 0,
+            };
+            self.data_len += 1;
+        }
+    }
+}
+
+impl Default for SimulatedController {
+    /// Default is a controller as after power on
+    fn default() -> Self {
+        SimulatedController::new()
+    }
+}
+
+/// A RAM plane of the [SimulatedController] in the logical orientation of a display, see
+/// [SimulatedController::ram_image].
+pub struct RamImage<'a> {
+    /// The RAM plane
+    plane: &'a [u8],
+    /// The number of rows of the display
+    rows: u16,
+    /// The number of columns of the display
+    cols: u16,
+    /// The rotation of the display
+    rotation: Rotation,
+}
+
+impl<'a> RamImage<'a> {
+    /// Create an image of a RAM plane
+    fn new(plane: &'a [u8], dimensions: &Dimensions, rotation: Rotation) -> Self {
+        RamImage {
+            plane,
+            rows: dimensions.rows,
+            cols: dimensions.cols,
+            rotation,
+        }
+    }
+}
+
+impl Image for RamImage<'_> {
+    fn image_size(&self) -> (u32, u32) {
+        logical_size(self.rows, self.cols, self.rotation)
+    }
+
+    fn max_level(&self) -> u8 {
+        1
+    }
+
+    fn level(&self, x: u32, y: u32) -> u8 {
+        // Locate the pixel in a display buffer, then find where the buffer was written in RAM
+        let row_len = u32::from(self.cols) / 8;
+        let (index, bit) = rotation(x, y, self.cols.into(), self.rows.into(), self.rotation);
+        let (row, column) = (index / row_len, index % row_len);
+        if row >= u32::from(self.rows) {
+            return 0;
+        }
+
+        let byte = self.plane[row as usize * RAM_ROW_LEN + column as usize];
+        u8::from(byte & bit != 0)
+    }
+}
+
+/// Returns the number of parameter bytes of a command, or 0 for commands that are not modelled
+fn parameter_len(command: u8) -> usize {
+    match command {
+        0x01 => 3,
+        0x10 | 0x11 | 0x21 | 0x22 | 0x41 | 0x46 | 0x47 => 1,
+        0x1A | 0x4E | 0x4F => 2,
+        0x32 => LUT_LEN,
+        0x44 | 0x45 => 4,
+        _ => 0,
+    }
+}
+
+/// Move an address by a step within the inclusive range `start..=end`.
+///
+/// Returns the new address, and true if the address wrapped around to the other end.
+fn step(address: u16, start: u16, end: u16, step: u16, increment: bool) -> (u16, bool) {
+    match increment {
+        true if address >= end => (start, true),
+        true => (address + step, false),
+        false if address <= start => (end, true),
+        false => (address - step, false),
+    }
+}
+
+/// Returns the value of a pixel in a RAM plane, or `false` outside of the RAM
+fn pixel(plane: &[u8], x: u16, y: u16) -> bool {
+    if x >= MAX_SOURCE_OUTPUTS || y >= MAX_GATE_OUTPUTS {
+        return false;
+    }
+
+    plane[y as usize * RAM_ROW_LEN + x as usize / 8] & (0x80 >> (x % 8)) != 0
+}
+
+/// Fill a RAM plane with the checker pattern of the auto write regular pattern commands.
+///
+/// Bit 7 is the value of the first step, bits 6-4 select the step height and bits 2-0 the step
+/// width. The largest setting of each covers the whole RAM.
+fn fill_pattern(plane: &mut [u8], pattern: u8) {
+    let first = pattern & 0x80 != 0;
+    let step_height = match (pattern >> 4) & 0b111 {
+        0b111 => MAX_GATE_OUTPUTS as usize,
+        height => 8 << height,
+    };
+    let step_width = match pattern & 0b111 {
+        0b111 => MAX_SOURCE_OUTPUTS as usize,
+        width => 8 << width,
+    };
+
+    for (y, row) in plane.chunks_mut(RAM_ROW_LEN).enumerate() {
+        for (column, byte) in row.iter_mut().enumerate() {
+            // The steps are at least eight pixels wide, so every byte lies within a single step
+            let odd_step = (column * 8 / step_width + y / step_height) % 2 == 1;
+            *byte = match first != odd_step {
+                true => 0xFF,
+                false => 0x00,
+            };
+        }
+    }
+}
+
+/// Copy a RAM plane to the panel, applying the RAM content option of the plane
+fn show(ram: &[u8], displayed: &mut [u8], option: u8) {
+    match option {
+        // Bypass the RAM content as 0
+        0b0100 => displayed.fill(0),
+        // Invert the RAM content
+        0b1000 => {
+            for (displayed, byte) in displayed.iter_mut().zip(ram) {
+                *displayed = !byte;
+            }
+        }
+        _ => displayed.copy_from_slice(ram),
+    }
+}
+
+/// Implement the DisplayInterface functions
+impl DisplayInterface for SimulatedController {
+    type Error = Infallible;
+
+    fn send_command(&mut self, command: u8) -> Result<(), Self::Error> {
+        self.command(command);
+
+        Ok(())
+    }
+
+    fn send_data(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        for &byte in data {
+            self.data(byte);
+        }
+
+        Ok(())
+    }
+
+    fn send_data_without_busy_wait(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        self.send_data(data)
+    }
+
+    fn read_command(
+        &mut self,
+        command: u8,
+        dummy: &mut [u8],
+        data: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.command(command);
+        self.read(dummy);
+        self.read(data);
+
+        Ok(())
+    }
+
+    fn reset<D: embedded_hal::delay::DelayNs>(
+        &mut self,
+        _delay: &mut D,
+    ) -> Result<(), Self::Error> {
+        self.reset_hardware();
+
+        Ok(())
+    }
+
+    fn wake(&mut self) -> Result<(), Self::Error> {
+        self.reset_hardware();
+
+        Ok(())
+    }
+
+    fn busy_wait(&mut self) -> Result<(), Self::Error> {
+        // The simulated controller is never busy
+        Ok(())
+    }
+}
+
+/// Implement the async DisplayInterface functions
+#[cfg(feature = "async")]
+impl crate::asynch::interface::DisplayInterface for SimulatedController {
+    type Error = Infallible;
+
+    async fn send_command(&mut self, command: u8) -> Result<(), Self::Error> {
+        DisplayInterface::send_command(self, command)
+    }
+
+    async fn send_data(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        DisplayInterface::send_data(self, data)
+    }
+
+    async fn send_data_without_busy_wait(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        DisplayInterface::send_data_without_busy_wait(self, data)
+    }
+
+    async fn read_command(
+        &mut self,
+        command: u8,
+        dummy: &mut [u8],
+        data: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        DisplayInterface::read_command(self, command, dummy, data)
+    }
+
+    async fn reset<D: embedded_hal_async::delay::DelayNs>(
+        &mut self,
+        _delay: &mut D,
+    ) -> Result<(), Self::Error> {
+        self.reset_hardware();
+
+        Ok(())
+    }
+
+    async fn wake(&mut self) -> Result<(), Self::Error> {
+        DisplayInterface::wake(self)
+    }
+
+    async fn busy_wait(&mut self) -> Result<(), Self::Error> {
+        DisplayInterface::busy_wait(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basic_display::DisplayUpdateMode;
+    use crate::command::{DataEntryMode, DisplayCommands, IncrementAxis};
+    use crate::config::Builder;
+
+    /// Non-square dimensions, so swapping rows and columns is caught
+    const DIMENSIONS: Dimensions = Dimensions { rows: 16, cols: 24 };
+
+    /// Returns the RAM position of every black pixel shown on the panel
+    fn black_pixels(controller: &SimulatedController) -> Vec<(u16, u16)> {
+        let mut pixels = Vec::new();
+        for y in 0..DIMENSIONS.rows {
+            for x in 0..DIMENSIONS.cols {
+                if !controller.displayed_pixel(RamPlane::BlackAndWhite, x, y) {
+                    pixels.push((x, y));
+                }
+            }
+        }
+
+        pixels
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn draws_at_every_rotation() {
+        use crate::display::Display;
+        use embedded_graphics_core::{pixelcolor::BinaryColor, prelude::*};
+
+        // The logical pixel drawn, and the RAM position it is expected at. The display buffer
+        // holds the columns from right to left.
+        let cases = [
+            (Rotation::Rotate0, (0, 0), (23, 0)),
+            (Rotation::Rotate0, (1, 2), (22, 2)),
+            (Rotation::Rotate0, (23, 15), (0, 15)),
+            (Rotation::Rotate90, (0, 0), (23, 15)),
+            (Rotation::Rotate90, (1, 2), (21, 14)),
+            (Rotation::Rotate90, (15, 23), (0, 0)),
+            (Rotation::Rotate180, (0, 0), (0, 15)),
+            (Rotation::Rotate180, (1, 2), (1, 13)),
+            (Rotation::Rotate180, (23, 15), (23, 0)),
+            (Rotation::Rotate270, (0, 0), (0, 0)),
+            (Rotation::Rotate270, (1, 2), (2, 1)),
+            (Rotation::Rotate270, (15, 23), (23, 15)),
+        ];
+
+        for (rotation, (x, y), expected) in cases {
+            let config = Builder::new()
+                .dimensions(DIMENSIONS)
+                .rotation(rotation)
+                .auto_update(false)
+                .build()
+                .unwrap();
+            let mut buffer = [0xFF; 16 * 24 / 8];
+            let mut display = Display::new(SimulatedController::new(), &mut buffer, config);
+            display.init().unwrap();
+
+            Pixel(Point::new(x, y), BinaryColor::On)
+                .draw(&mut display)
+                .unwrap();
+            display.update(DisplayUpdateMode::Slow).unwrap();
+
+            let controller = display.interface_mut();
+            assert_eq!(
+                black_pixels(controller),
+                [expected],
+                "{rotation:?} ({x}, {y})"
+            );
+
+            // The exported image finds the pixel where it was drawn
+            let image = controller.displayed_image(RamPlane::BlackAndWhite, &DIMENSIONS, rotation);
+            assert_eq!(
+                image.level(x as u32, y as u32),
+                0,
+                "{rotation:?} ({x}, {y})"
+            );
+        }
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn update_dirty_sends_only_dirty_bytes() {
+        use crate::display::Display;
+        use crate::trace::{TraceEntry, TraceEvent, TraceRecorder};
+        use core::cell::Cell;
+        use embedded_graphics_core::{pixelcolor::BinaryColor, prelude::*};
+
+        // Count the bytes written to the black and white RAM
+        let command = Cell::new(0);
+        let ram_bytes = Cell::new(0);
+        let recorder =
+            TraceRecorder::new(
+                SimulatedController::new(),
+                |entry: &TraceEntry| match entry.event {
+                    TraceEvent::Command(opcode) => command.set(opcode),
+                    TraceEvent::Data(data) if command.get() == 0x24 => {
+                        ram_bytes.set(ram_bytes.get() + data.len())
+                    }
+                    _ => {}
+                },
+            );
+
+        let config = Builder::new()
+            .dimensions(DIMENSIONS)
+            .auto_update(false)
+            .build()
+            .unwrap();
+        let mut buffer = [0xFF; 16 * 24 / 8];
+        let mut display = Display::new(recorder, &mut buffer, config);
+        display.init().unwrap();
+        display.update(DisplayUpdateMode::Slow).unwrap();
+        ram_bytes.set(0);
+
+        // Two pixels in the same byte column, four rows apart
+        Pixel(Point::new(1, 2), BinaryColor::On)
+            .draw(&mut display)
+            .unwrap();
+        Pixel(Point::new(1, 5), BinaryColor::On)
+            .draw(&mut display)
+            .unwrap();
+        display.update_dirty(DisplayUpdateMode::Slow).unwrap();
+
+        assert_eq!(ram_bytes.get(), 4);
+        let controller = display.interface_mut().inner();
+        assert_eq!(black_pixels(controller), [(22, 2), (22, 5)]);
+        assert_eq!(controller.ram_window(), ((0, 23), (0, 15)));
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn update_dirty_after_deep_sleep_sends_everything() {
+        use crate::command::DeepSleepMode;
+        use crate::display::Display;
+        use embedded_graphics_core::{pixelcolor::BinaryColor, prelude::*};
+
+        let config = Builder::new()
+            .dimensions(DIMENSIONS)
+            .auto_update(false)
+            .build()
+            .unwrap();
+        let mut buffer = [0xFF; 16 * 24 / 8];
+        let mut display = Display::new(SimulatedController::new(), &mut buffer, config);
+        display.init().unwrap();
+        display.update(DisplayUpdateMode::Slow).unwrap();
+
+        // The RAM is wiped in deep sleep
+        display.sleep(DeepSleepMode::DiscardRAM).unwrap();
+        assert!(display.ram_invalid());
+
+        Pixel(Point::new(0, 0), BinaryColor::On)
+            .draw(&mut display)
+            .unwrap();
+        display.update_dirty(DisplayUpdateMode::Slow).unwrap();
+
+        assert_eq!(black_pixels(display.interface_mut()), [(23, 0)]);
+    }
+
+    #[test]
+    fn update_region_lands_in_window() {
+        let config = Builder::new().dimensions(DIMENSIONS).build().unwrap();
+        let mut display =
+            crate::basic_display::BasicDisplay::new(SimulatedController::new(), config);
+        display.init().unwrap();
+        display
+            .update(Some(&[0xFF; 16 * 24 / 8]), None, DisplayUpdateMode::Slow)
+            .unwrap();
+
+        // Two rows of the middle byte column
+        let region = crate::basic_display::Region {
+            x: 8,
+            y: 4,
+            width: 8,
+            height: 2,
+        };
+        display
+            .update_region(region, Some(&[0x7F, 0xFE]), None, DisplayUpdateMode::Slow)
+            .unwrap();
+
+        assert_eq!(black_pixels(display.interface_mut()), [(8, 4), (15, 5)]);
+    }
+
+    #[test]
+    fn counters_wrap_around_window() {
+        let mut controller = SimulatedController::new();
+        controller
+            .set_data_entry_mode(
+                DataEntryMode::IncrementXIncrementY,
+                IncrementAxis::Horizontal,
+            )
+            .unwrap();
+        controller.set_ram_x_address(8, 23).unwrap();
+        controller.set_ram_y_address(2, 3).unwrap();
+        controller.set_ram_x_count(8).unwrap();
+        controller.set_ram_y_count(2).unwrap();
+
+        // One byte more than the window holds, the last byte wraps to the start of the window
+        controller
+            .write_ram_black_and_white(&[0x01, 0x02, 0x03, 0x04, 0x05])
+            .unwrap();
+
+        let ram = controller.ram(RamPlane::BlackAndWhite);
+        assert_eq!(
+            ram[2 * RAM_ROW_LEN..2 * RAM_ROW_LEN + 4],
+            [0x00, 0x05, 0x02, 0x00]
+        );
+        assert_eq!(
+            ram[3 * RAM_ROW_LEN..3 * RAM_ROW_LEN + 4],
+            [0x00, 0x03, 0x04, 0x00]
+        );
+        assert_eq!(controller.address_counters(), (16, 2));
+    }
+
+    #[test]
+    fn writes_in_every_data_entry_mode() {
+        use DataEntryMode::*;
+        use IncrementAxis::*;
+
+        // The counters to start from, and the byte column and row every written byte lands at
+        let cases = [
+            (
+                IncrementXIncrementY,
+                Horizontal,
+                (8, 4),
+                [(1, 4), (2, 4), (1, 5), (2, 5)],
+            ),
+            (
+                DecrementXIncrementY,
+                Horizontal,
+                (16, 4),
+                [(2, 4), (1, 4), (2, 5), (1, 5)],
+            ),
+            (
+                IncrementXDecrementY,
+                Horizontal,
+                (8, 5),
+                [(1, 5), (2, 5), (1, 4), (2, 4)],
+            ),
+            (
+                DecrementXDecrementY,
+                Horizontal,
+                (16, 5),
+                [(2, 5), (1, 5), (2, 4), (1, 4)],
+            ),
+            (
+                IncrementXIncrementY,
+                Vertical,
+                (8, 4),
+                [(1, 4), (1, 5), (2, 4), (2, 5)],
+            ),
+            (
+                DecrementXIncrementY,
+                Vertical,
+                (16, 4),
+                [(2, 4), (2, 5), (1, 4), (1, 5)],
+            ),
+            (
+                IncrementXDecrementY,
+                Vertical,
+                (8, 5),
+                [(1, 5), (1, 4), (2, 5), (2, 4)],
+            ),
+            (
+                DecrementXDecrementY,
+                Vertical,
+                (16, 5),
+                [(2, 5), (2, 4), (1, 5), (1, 4)],
+            ),
+        ];
+
+        for (mode, axis, (x, y), expected) in cases {
+            let mut controller = SimulatedController::new();
+            controller.set_data_entry_mode(mode, axis).unwrap();
+            controller.set_ram_x_address(8, 23).unwrap();
+            controller.set_ram_y_address(4, 5).unwrap();
+            controller.set_ram_x_count(x).unwrap();
+            controller.set_ram_y_count(y).unwrap();
+
+            controller
+                .write_ram_black_and_white(&[0x01, 0x02, 0x03, 0x04])
+                .unwrap();
+
+            let ram = controller.ram(RamPlane::BlackAndWhite);
+            for (byte, (column, row)) in (1..).zip(expected) {
+                assert_eq!(ram[row * RAM_ROW_LEN + column], byte, "{mode:?} {axis:?}");
+            }
+            // Filling the window wraps both counters back to the start
+            assert_eq!(controller.address_counters(), (x, y), "{mode:?} {axis:?}");
+        }
+    }
+}
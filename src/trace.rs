@@ -0,0 +1,533 @@
+//! This module provides tools to debug the commands sent to the SSD1677 controller.
+//!
+//! The [TraceRecorder] wraps any [DisplayInterface], and reports every command, data and read to
+//! a closure as a [TraceEntry], optionally with a timestamp. The closure can log the entries, or
+//! store them for later.
+//!
+//! The [CommandDecoder] turns a stream of [Frame]s, such as a capture of the SPI bus by a logic
+//! analyzer, back into [DecodedCommand]s. These print the name of every command with its
+//! arguments decoded, which makes it easy to compare an init sequence against vendor sample code.
+//! [DecodedCommand::command] returns the typed [Command], to replay it with
+//! [DisplayInterface::execute].
+//! Captures of the 3-wire SPI mode can be split into frames with [unpack_frames].
+//!
+//! ```
+//! use ssd1677::trace::{decode, Frame};
+//!
+//! // Data entry mode setting, incrementing X then Y
+//! let frames = [
+//!     Frame { is_data: false, byte: 0x11 },
+//!     Frame { is_data: true, byte: 0x03 },
+//! ];
+//!
+//! for command in decode(frames) {
+//!     // Prints `0x11 DataEntryModeSetting(IncrementXIncrementY, Horizontal)`
+//!     println!("{}", command);
+//! }
+//! ```
+use core::fmt;
+use core::iter::Peekable;
+
+use crate::command::{Command, MAX_PARAMETERS_LEN};
+use crate::interface::DisplayInterface;
+
+/// Something that happened on the interface to the controller
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TraceEvent<'a> {
+    /// A command was sent
+    Command(u8),
+    /// Data was sent for the last command
+    Data(&'a [u8]),
+    /// Data was read for the last command
+    Read(&'a [u8]),
+    /// The controller was reset
+    Reset,
+    /// The controller was woken from deep sleep
+    Wake,
+}
+
+impl<'a> TraceEvent<'a> {
+    /// Returns the frames sent or read on the bus for the event.
+    ///
+    /// Resetting and waking the controller do not use the bus, so they have no frames.
+    pub fn frames(&self) -> impl Iterator<Item = Frame> + 'a {
+        let (command, data): (Option<u8>, &'a [u8]) = match *self {
+            TraceEvent::Command(command) => (Some(command), &[]),
+            TraceEvent::Data(data) | TraceEvent::Read(data) => (None, data),
+            TraceEvent::Reset | TraceEvent::Wake => (None, &[]),
+        };
+
+        let command = command.map(|byte| Frame {
+            is_data: false,
+            byte,
+        });
+        let data = data.iter().map(|&byte| Frame {
+            is_data: true,
+            byte,
+        });
+
+        command.into_iter().chain(data)
+    }
+}
+
+/// An event recorded by the [TraceRecorder]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TraceEntry<'a> {
+    /// The time the event started at in milliseconds, if the recorder has a clock
+    pub timestamp_ms: Option<u32>,
+    /// The event
+    pub event: TraceEvent<'a>,
+}
+
+/// A [DisplayInterface] that records everything sent over the interface it wraps.
+///
+/// Every event is passed to the `record` closure as a [TraceEntry], before it is forwarded to
+/// the wrapped interface. Data that is read is recorded once the read completed.
+///
+/// ```
+/// use ssd1677::interface::DisplayInterface;
+/// use ssd1677::trace::{TraceEntry, TraceRecorder};
+///
+/// fn trace<I: DisplayInterface>(interface: I) -> impl DisplayInterface {
+///     TraceRecorder::new(interface, |entry: &TraceEntry| {
+///         println!("{:?}", entry.event);
+///     })
+/// }
+/// ```
+pub struct TraceRecorder<I, R, C = fn() -> u32> {
+    /// The interface to the controller
+    interface: I,
+    /// The closure every event is recorded with
+    record: R,
+    /// The clock to timestamp the events with
+    clock: Option<C>,
+}
+
+impl<I, R> TraceRecorder<I, R>
+where
+    R: FnMut(&TraceEntry<'_>),
+{
+    /// Create a new `TraceRecorder` without timestamps.
+    ///
+    /// # Arguments
+    ///
+    /// * `interface` - The interface to the controller to record.
+    /// * `record` - Closure called with every event on the interface.
+    pub fn new(interface: I, record: R) -> Self {
+        Self {
+            interface,
+            record,
+            clock: None,
+        }
+    }
+}
+
+impl<I, R, C> TraceRecorder<I, R, C>
+where
+    R: FnMut(&TraceEntry<'_>),
+    C: FnMut() -> u32,
+{
+    /// Timestamp the recorded events with a clock.
+    ///
+    /// # Arguments
+    ///
+    /// * `clock` - Closure returning the current time in milliseconds, such as
+    ///   `|| Instant::now().as_millis() as u32` on embassy.
+    pub fn with_clock<C2: FnMut() -> u32>(self, clock: C2) -> TraceRecorder<I, R, C2> {
+        TraceRecorder {
+            interface: self.interface,
+            record: self.record,
+            clock: Some(clock),
+        }
+    }
+
+    /// Returns a reference to the wrapped interface
+    pub fn inner(&self) -> &I {
+        &self.interface
+    }
+
+    /// Returns a mutable reference to the wrapped interface, anything sent through it directly
+    /// is not recorded
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.interface
+    }
+
+    /// Release the wrapped interface
+    pub fn into_inner(self) -> I {
+        self.interface
+    }
+
+    /// Record an event with the current time
+    fn record(&mut self, event: TraceEvent<'_>) {
+        let timestamp_ms = self.clock.as_mut().map(|clock| clock());
+
+        (self.record)(&TraceEntry {
+            timestamp_ms,
+            event,
+        });
+    }
+}
+
+/// Implement the DisplayInterface functions
+impl<I, R, C> DisplayInterface for TraceRecorder<I, R, C>
+where
+    I: DisplayInterface,
+    R: FnMut(&TraceEntry<'_>),
+    C: FnMut() -> u32,
+{
+    type Error = I::Error;
+
+    fn send_command(&mut self, command: u8) -> Result<(), Self::Error> {
+        self.record(TraceEvent::Command(command));
+        self.interface.send_command(command)
+    }
+
+    fn send_data(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        self.record(TraceEvent::Data(data));
+        self.interface.send_data(data)
+    }
+
+    fn send_data_without_busy_wait(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        self.record(TraceEvent::Data(data));
+        self.interface.send_data_without_busy_wait(data)
+    }
+
+    fn read_command(
+        &mut self,
+        command: u8,
+        dummy: &mut [u8],
+        data: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.record(TraceEvent::Command(command));
+        self.interface.read_command(command, dummy, data)?;
+        self.record(TraceEvent::Read(data));
+
+        Ok(())
+    }
+
+    fn reset<D: embedded_hal::delay::DelayNs>(&mut self, delay: &mut D) -> Result<(), Self::Error> {
+        self.record(TraceEvent::Reset);
+        self.interface.reset(delay)
+    }
+
+    fn wake(&mut self) -> Result<(), Self::Error> {
+        self.record(TraceEvent::Wake);
+        self.interface.wake()
+    }
+
+    fn busy_wait(&mut self) -> Result<(), Self::Error> {
+        self.interface.busy_wait()
+    }
+}
+
+/// Implement the async DisplayInterface functions
+#[cfg(feature = "async")]
+impl<I, R, C> crate::asynch::interface::DisplayInterface for TraceRecorder<I, R, C>
+where
+    I: crate::asynch::interface::DisplayInterface,
+    R: FnMut(&TraceEntry<'_>),
+    C: FnMut() -> u32,
+{
+    type Error = I::Error;
+
+    async fn send_command(&mut self, command: u8) -> Result<(), Self::Error> {
+        self.record(TraceEvent::Command(command));
+        self.interface.send_command(command).await
+    }
+
+    async fn send_data(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        self.record(TraceEvent::Data(data));
+        self.interface.send_data(data).await
+    }
+
+    async fn send_data_without_busy_wait(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        self.record(TraceEvent::Data(data));
+        self.interface.send_data_without_busy_wait(data).await
+    }
+
+    async fn read_command(
+        &mut self,
+        command: u8,
+        dummy: &mut [u8],
+        data: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.record(TraceEvent::Command(command));
+        self.interface.read_command(command, dummy, data).await?;
+        self.record(TraceEvent::Read(data));
+
+        Ok(())
+    }
+
+    async fn reset<D: embedded_hal_async::delay::DelayNs>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<(), Self::Error> {
+        self.record(TraceEvent::Reset);
+        self.interface.reset(delay).await
+    }
+
+    async fn wake(&mut self) -> Result<(), Self::Error> {
+        self.record(TraceEvent::Wake);
+        self.interface.wake().await
+    }
+
+    async fn busy_wait(&mut self) -> Result<(), Self::Error> {
+        self.interface.busy_wait().await
+    }
+}
+
+/// A byte on the bus, with the level of the data/command signal it was sent with
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Frame {
+    /// The data/command signal, `true` for data
+    pub is_data: bool,
+    /// The byte
+    pub byte: u8,
+}
+
+/// Split a capture of the 3-wire SPI mode into frames.
+///
+/// Every frame is nine bits, the data/command bit followed by the byte, most significant bit
+/// first. Bits left over at the end of the capture are padding, and are ignored.
+pub fn unpack_frames(bytes: &[u8]) -> impl Iterator<Item = Frame> + '_ {
+    let bit = move |index: usize| (bytes[index / 8] >> (7 - index % 8)) & 1;
+
+    (0..bytes.len() * 8 / 9).map(move |frame| {
+        let start = frame * 9;
+        let byte = (1..9).fold(0, |byte, offset| (byte << 1) | bit(start + offset));
+
+        Frame {
+            is_data: bit(start) == 1,
+            byte,
+        }
+    })
+}
+
+/// The number of argument bytes kept for a [DecodedCommand], enough for a full LUT
+pub const MAX_ARGUMENTS: usize = MAX_PARAMETERS_LEN;
+
+/// A command decoded from a stream of [Frame]s, with the data sent for it.
+///
+/// Print a decoded command to show its opcode, name and decoded arguments. Arguments that can
+/// not be decoded are shown as bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DecodedCommand {
+    /// The opcode of the command
+    opcode: u8,
+    /// The first data bytes sent for the command
+    arguments: [u8; MAX_ARGUMENTS],
+    /// The number of data bytes sent for the command
+    data_len: usize,
+}
+
+impl DecodedCommand {
+    /// Returns the opcode of the command
+    pub fn opcode(&self) -> u8 {
+        self.opcode
+    }
+
+    /// Returns the name of the command as in the datasheet, or `Unknown` for unknown opcodes
+    pub fn name(&self) -> &'static str {
+        command_name(self.opcode)
+    }
+
+    /// Returns the data sent for the command, up to [MAX_ARGUMENTS] bytes
+    pub fn arguments(&self) -> &[u8] {
+        &self.arguments[..self.data_len.min(MAX_ARGUMENTS)]
+    }
+
+    /// Returns the number of data bytes sent for the command, such as the length of a RAM write
+    pub fn data_len(&self) -> usize {
+        self.data_len
+    }
+
+    /// Returns the typed command, or `None` if the opcode is not known, the arguments are not
+    /// valid for it, or more than [MAX_ARGUMENTS] bytes were sent
+    pub fn command(&self) -> Option<Command<'_>> {
+        if self.data_len > MAX_ARGUMENTS {
+            return None;
+        }
+
+        Command::decode(self.opcode, self.arguments())
+    }
+
+    /// Add a data byte to the command
+    fn push(&mut self, byte: u8) {
+        if let Some(argument) = self.arguments.get_mut(self.data_len) {
+            *argument = byte;
+        }
+        self.data_len += 1;
+    }
+}
+
+impl fmt::Display for DecodedCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#04x} {}", self.opcode, self.name())?;
+
+        let arguments = self.arguments();
+        if arguments.is_empty() {
+            return Ok(());
+        }
+
+        // Write the decoded arguments, falling back to the bytes if they are not valid
+        match self.command() {
+            Some(Command::DriverOutputControl(max_gate_line, scanning)) => {
+                let gate_lines = (max_gate_line & 0x3FF) + 1;
+                return write!(
+                    f,
+                    "(gate lines: {}, scanning: {:#04x})",
+                    gate_lines, scanning
+                );
+            }
+            Some(Command::GateDrivingVoltage(voltage)) => {
+                return write!(f, "({} mV)", voltage.millivolts());
+            }
+            Some(Command::SourceDrivingVoltage(voltage)) => {
+                return write!(
+                    f,
+                    "(VSH1: {} mV, VSH2: {} mV, VSL: {} mV)",
+                    voltage.vsh1_millivolts(),
+                    voltage.vsh2_millivolts(),
+                    voltage.vsl_millivolts()
+                );
+            }
+            Some(Command::BoosterSoftStartControl(inrush)) => return write!(f, "({:?})", inrush),
+            Some(Command::DeepSleepMode(mode)) => return write!(f, "({:?})", mode),
+            Some(Command::DataEntryMode(mode, axis)) => {
+                return write!(f, "({:?}, {:?})", mode, axis);
+            }
+            Some(Command::TemperatureSensor(sensor)) => return write!(f, "({:?})", sensor),
+            Some(Command::WriteTemperature(temperature)) => {
+                return write!(f, "({} °C)", temperature.as_f32());
+            }
+            Some(Command::UpdateDisplayOption1(black_and_white, red)) => {
+                return write!(
+                    f,
+                    "(black and white: {:?}, red: {:?})",
+                    black_and_white, red
+                );
+            }
+            Some(Command::UpdateDisplayOption2(sequence)) => {
+                return write!(f, "(sequence: {:#04x})", sequence.bits());
+            }
+            Some(Command::WriteVcom(voltage)) => {
+                return write!(f, "({} mV)", voltage.millivolts());
+            }
+            Some(Command::BorderWaveformControl(vdb_option, fixed_level, transition)) => {
+                return write!(f, "({:?}, {:?}, {:?})", vdb_option, fixed_level, transition);
+            }
+            Some(Command::ReadRamOption(plane)) => return write!(f, "({:?})", plane),
+            Some(Command::RamXAddress(start, end) | Command::RamYAddress(start, end)) => {
+                return write!(f, "(start: {}, end: {})", start & 0x3FF, end & 0x3FF);
+            }
+            Some(
+                Command::AutoWriteRedRamRegularPattern(pattern)
+                | Command::AutoWriteBlackAndWhiteRamRegularPattern(pattern),
+            ) => return write!(f, "(pattern: {:#04x})", pattern),
+            Some(Command::RamXCount(offset) | Command::RamYCount(offset)) => {
+                return write!(f, "({})", offset & 0x3FF);
+            }
+            // The data of RAM and LUT writes is too long to print
+            _ if matches!(self.opcode, 0x24 | 0x26 | 0x32) => {
+                return write!(f, "({} bytes)", self.data_len);
+            }
+            _ => {}
+        }
+
+        write!(f, "({:02x?}", arguments)?;
+        if self.data_len > arguments.len() {
+            write!(f, ", {} bytes", self.data_len)?;
+        }
+        write!(f, ")")
+    }
+}
+
+/// Returns the datasheet name of a command
+fn command_name(opcode: u8) -> &'static str {
+    match opcode {
+        0x01 => "DriverOutputControl",
+        0x03 => "GateDrivingVoltage",
+        0x04 => "SourceDrivingVoltage",
+        0x0C => "BoosterSoftStartControl",
+        0x10 => "DeepSleepMode",
+        0x11 => "DataEntryModeSetting",
+        0x12 => "SoftwareReset",
+        0x18 => "TemperatureSensorSelection",
+        0x1A => "WriteTemperatureRegister",
+        0x1B => "ReadTemperatureRegister",
+        0x20 => "MasterActivation",
+        0x21 => "DisplayUpdateControl1",
+        0x22 => "DisplayUpdateControl2",
+        0x24 => "WriteRamBlackAndWhite",
+        0x26 => "WriteRamRed",
+        0x27 => "ReadRam",
+        0x2C => "WriteVcomRegister",
+        0x2D => "ReadOtpDisplayOption",
+        0x2E => "ReadUserId",
+        0x2F => "StatusBitRead",
+        0x32 => "WriteLutRegister",
+        0x3C => "BorderWaveformControl",
+        0x41 => "ReadRamOption",
+        0x44 => "SetRamXAddressStartEnd",
+        0x45 => "SetRamYAddressStartEnd",
+        0x46 => "AutoWriteRedRamRegularPattern",
+        0x47 => "AutoWriteBlackAndWhiteRamRegularPattern",
+        0x4E => "SetRamXAddressCounter",
+        0x4F => "SetRamYAddressCounter",
+        0x7F => "Nop",
+        _ => "Unknown",
+    }
+}
+
+/// An iterator decoding a stream of [Frame]s into [DecodedCommand]s, see [decode].
+pub struct CommandDecoder<It>
+where
+    It: Iterator<Item = Frame>,
+{
+    /// The frames left to decode
+    frames: Peekable<It>,
+}
+
+impl<It> Iterator for CommandDecoder<It>
+where
+    It: Iterator<Item = Frame>,
+{
+    type Item = DecodedCommand;
+
+    fn next(&mut self) -> Option<DecodedCommand> {
+        // Data without a command, such as at the start of a capture, is skipped
+        let opcode = loop {
+            let frame = self.frames.next()?;
+            if !frame.is_data {
+                break frame.byte;
+            }
+        };
+
+        let mut command = DecodedCommand {
+            opcode,
+            arguments: [0; MAX_ARGUMENTS],
+            data_len: 0,
+        };
+        while let Some(frame) = self.frames.next_if(|frame| frame.is_data) {
+            command.push(frame.byte);
+        }
+
+        Some(command)
+    }
+}
+
+/// Decode a stream of [Frame]s into the commands sent to the controller.
+///
+/// Every command frame starts a new command, and the data frames after it are its arguments.
+///
+/// # Arguments
+///
+/// * `frames` - The frames on the bus, in the order they were sent.
+pub fn decode<It>(frames: It) -> CommandDecoder<It::IntoIter>
+where
+    It: IntoIterator<Item = Frame>,
+{
+    CommandDecoder {
+        frames: frames.into_iter().peekable(),
+    }
+}
@@ -0,0 +1,311 @@
+//! This module provides the [TriColorDisplay] struct for managing
+//! a black, white and red display with graphics capabilities.
+//!
+//! A tri-color panel has two RAM planes in the controller: the black and white plane, and the
+//! red plane. [TriColorDisplay] holds a buffer for each plane, and draws pixels of the
+//! [TriColor] type into them.
+//!
+//! # Features
+//!
+//! - **Graphics Support**: When the `graphics` feature is enabled, [TriColorDisplay] implements
+//!   the [DrawTarget] trait from the [embedded-graphics-core] crate, with [TriColor] as the
+//!   color type.
+//!
+//! [DrawTarget]: https://docs.rs/embedded-graphics-core/0.4.0/embedded_graphics_core/draw_target/trait.DrawTarget.html
+//! [embedded-graphics-core]: https://crates.io/crates/embedded-graphics-core
+use crate::basic_display::{BasicDisplay, DisplayUpdateMode, Rotation};
+use crate::config;
+use crate::display::rotation;
+use crate::interface::DisplayInterface;
+
+#[cfg(feature = "graphics")]
+use embedded_graphics_core::{pixelcolor::raw::RawU2, prelude::*};
+
+#[cfg(feature = "defmt")]
+use defmt::*;
+
+/// The color of a pixel on a black, white and red display.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TriColor {
+    /// A black pixel
+    Black,
+    /// A white pixel
+    White,
+    /// A red pixel
+    Red,
+}
+
+impl TriColor {
+    /// Returns the values to fill the black and white, and the red buffer with for this color
+    fn fill_values(self) -> (u8, u8) {
+        // A set bit in the black and white RAM is white, a set bit in the red RAM is red
+        match self {
+            TriColor::Black => (0x00, 0x00),
+            TriColor::White => (0xFF, 0x00),
+            TriColor::Red => (0xFF, 0xFF),
+        }
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl PixelColor for TriColor {
+    type Raw = RawU2;
+}
+
+/// A display that holds a black and white, and a red buffer for drawing into and updating
+/// the display.
+pub struct TriColorDisplay<'a, I>
+where
+    I: DisplayInterface,
+{
+    display: BasicDisplay<I>, // The underlying display interface
+    bw_buffer: &'a mut [u8],  // The buffer for black and white pixel data
+    red_buffer: &'a mut [u8], // The buffer for red pixel data
+}
+
+impl<'a, I> TriColorDisplay<'a, I>
+where
+    I: DisplayInterface,
+{
+    /// Creates a new [TriColorDisplay] instance.
+    ///
+    /// This function creates a [BasicDisplay] using the provided interface and configuration,
+    /// then promotes it to a [TriColorDisplay] using the provided buffers.
+    ///
+    /// # Parameters
+    ///
+    /// - `interface`: An instance of the interface type `I` that will be used for communication
+    ///   with the display hardware, such as [Interface4Pin].
+    /// - `bw_buffer`: A mutable reference to the buffer for black and white pixel data.
+    /// - `red_buffer`: A mutable reference to the buffer for red pixel data.
+    /// - `config`: An instance of [Config] that contains the configuration settings for
+    ///   the display.
+    ///
+    /// Both buffers must be `rows * cols / 8` bytes long.
+    ///
+    /// # Returns
+    ///
+    /// Returns a new [TriColorDisplay] instance that is ready for use.
+    ///
+    /// [Interface4Pin]: crate::interface::Interface4Pin
+    /// [Config]: crate::config::Config
+    pub fn new(
+        interface: I,
+        bw_buffer: &'a mut [u8],
+        red_buffer: &'a mut [u8],
+        config: config::Config,
+    ) -> Self {
+        // First create a basic display
+        let d = BasicDisplay::new(interface, config);
+
+        // Promote the basic display to a TriColorDisplay
+        TriColorDisplay::from_basic_display(d, bw_buffer, red_buffer)
+    }
+
+    /// Promote a [BasicDisplay] to a [TriColorDisplay].
+    ///
+    /// Both buffers should be of length `rows * cols / 8`, where `rows` and `cols` are the
+    /// dimensions of the display.
+    ///
+    /// # Arguments
+    ///
+    /// * `display` - The underlying display instance.
+    /// * `bw_buffer` - A mutable reference to the buffer for black and white pixel data.
+    /// * `red_buffer` - A mutable reference to the buffer for red pixel data.
+    pub fn from_basic_display(
+        display: BasicDisplay<I>,
+        bw_buffer: &'a mut [u8],
+        red_buffer: &'a mut [u8],
+    ) -> Self {
+        TriColorDisplay {
+            display,
+            bw_buffer,
+            red_buffer,
+        }
+    }
+
+    /// Update the display by writing both buffers to the controller.
+    ///
+    /// Red pixels are only driven by the full waveform of the panel, so tri-color panels
+    /// should be updated with [DisplayUpdateMode::Slow].
+    ///
+    /// # Arguments
+    ///
+    /// * `mode` - The kind of update to perform, see [DisplayUpdateMode] for details.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), <I as DisplayInterface>::Error>` - Returns `Ok(())` on success,
+    ///   or an error if the update fails.
+    pub fn update(
+        &mut self,
+        mode: DisplayUpdateMode,
+    ) -> Result<(), <I as DisplayInterface>::Error> {
+        self.display
+            .update(Some(self.bw_buffer), Some(self.red_buffer), mode)
+    }
+
+    /// Clear both buffers, filling them with a single color.
+    ///
+    /// # Arguments
+    ///
+    /// * `color` - The color to fill the buffers with, see [TriColor].
+    pub fn clear(&mut self, color: TriColor) -> Result<(), <I as DisplayInterface>::Error> {
+        // Figure out the fill values
+        let (bw_value, red_value) = color.fill_values();
+
+        self.bw_buffer.fill(bw_value);
+        self.red_buffer.fill(red_value);
+
+        // Refresh the display if auto_update is enabled
+        if self.display.config.auto_update {
+            self.update(DisplayUpdateMode::Slow)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Set a pixel at the specified coordinates to the given color.
+    ///
+    /// This method updates both buffers to reflect the color of the pixel at the
+    /// specified `(x, y)` coordinates, taking into account the current rotation
+    /// of the display.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - The x-coordinate of the pixel.
+    /// * `y` - The y-coordinate of the pixel.
+    /// * `color` - The color to set the pixel to, see [TriColor].
+    pub fn set_pixel(&mut self, x: u32, y: u32, color: TriColor) {
+        #[cfg(feature = "defmt")]
+        trace!(
+            "Setting pixel on (x: {}, y: {}) to `{}` with rotation {}",
+            x,
+            y,
+            color,
+            self.rotation()
+        );
+
+        // Find out the buffer index and bit value, both planes share the same layout
+        let (index, bit) = rotation(
+            x,
+            y,
+            self.cols() as u32,
+            self.rows() as u32,
+            self.rotation(),
+        );
+        let index = index as usize;
+
+        #[cfg(feature = "defmt")]
+        trace!("Setting pixel on index {} to {}", index, bit);
+
+        // Set the value in the display buffers
+        match color {
+            TriColor::Black => {
+                self.bw_buffer[index] &= !bit;
+                self.red_buffer[index] &= !bit;
+            }
+            TriColor::White => {
+                self.bw_buffer[index] |= bit;
+                self.red_buffer[index] &= !bit;
+            }
+            TriColor::Red => {
+                self.bw_buffer[index] |= bit;
+                self.red_buffer[index] |= bit;
+            }
+        }
+    }
+}
+
+impl<'a, I> core::ops::Deref for TriColorDisplay<'a, I>
+where
+    I: DisplayInterface,
+{
+    type Target = BasicDisplay<I>;
+
+    /// Dereference to access the underlying [BasicDisplay] instance.
+    fn deref(&self) -> &BasicDisplay<I> {
+        &self.display
+    }
+}
+
+impl<'a, I> core::ops::DerefMut for TriColorDisplay<'a, I>
+where
+    I: DisplayInterface,
+{
+    /// Mutably dereference to access the underlying [BasicDisplay] instance.
+    fn deref_mut(&mut self) -> &mut BasicDisplay<I> {
+        &mut self.display
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl<'a, I> DrawTarget for TriColorDisplay<'a, I>
+where
+    I: DisplayInterface,
+{
+    type Color = TriColor;
+    type Error = <I as DisplayInterface>::Error;
+
+    /// Draw pixels from an iterator onto the display.
+    ///
+    /// This method takes an iterator of [Pixel] items and sets the corresponding
+    /// pixels in the display buffers. After drawing, it updates the display to
+    /// reflect the changes if `auto_update` is enabled in the [Config](crate::config::Config).
+    ///
+    /// # Arguments
+    ///
+    /// * `pixels` - An iterator of [`Pixel<Self::Color>`][Pixel] items to draw on the display.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), Self::Error>` - Drawing into the buffers cannot fail, an error is only
+    ///   returned if the automatic update of the display fails.
+    ///
+    /// [Pixel]: https://docs.rs/embedded-graphics-core/0.4.0/embedded_graphics_core/struct.Pixel.html
+    fn draw_iter<Iter>(&mut self, pixels: Iter) -> Result<(), Self::Error>
+    where
+        Iter: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let size = self.size();
+
+        #[cfg(feature = "defmt")]
+        trace!("Drawing to the display");
+
+        // Draw the image pixel by pixel
+        for Pixel(Point { x, y }, color) in pixels {
+            let x = x as u32;
+            let y = y as u32;
+
+            if x < size.width && y < size.height {
+                self.set_pixel(x, y, color);
+            }
+        }
+
+        // Refresh the display if auto_update is enabled
+        if self.config.auto_update {
+            self.update(DisplayUpdateMode::Slow)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl<'a, I> OriginDimensions for TriColorDisplay<'a, I>
+where
+    I: DisplayInterface,
+{
+    /// Get the size of the display in pixels, taking the rotation into account.
+    fn size(&self) -> Size {
+        match self.rotation() {
+            Rotation::Rotate0 | Rotation::Rotate180 => {
+                Size::new(self.cols().into(), self.rows().into())
+            }
+            Rotation::Rotate90 | Rotation::Rotate270 => {
+                Size::new(self.rows().into(), self.cols().into())
+            }
+        }
+    }
+}
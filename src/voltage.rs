@@ -0,0 +1,375 @@
+//! This module provides validated voltage settings for the voltage commands of the SSD1677
+//! controller.
+//!
+//! Every type is constructed from millivolts, and only accepts voltages that the controller
+//! supports. The `new` constructors are `const` and panic on an invalid voltage, so a voltage
+//! given as a constant fails to compile instead of being sent to the controller:
+//!
+//! ```
+//! use ssd1677::voltage::{GateVoltage, SourceVoltage};
+//!
+//! const GATE: GateVoltage = GateVoltage::new(20_000);
+//! const SOURCE: SourceVoltage = SourceVoltage::new(15_000, 5_000, -15_000);
+//! ```
+//!
+//! The `from_millivolts` constructors return `None` instead, for voltages only known at runtime.
+
+/// Returns the voltage of a code in a range of evenly spaced codes, or `None` if the code is
+/// outside the range
+const fn code_voltage(code: u8, min: i32, max: i32, step: i32, base: u8) -> Option<i32> {
+    if code < base {
+        return None;
+    }
+
+    let millivolts = min + (code - base) as i32 * step;
+    if millivolts > max {
+        return None;
+    }
+
+    Some(millivolts)
+}
+
+/// Returns the register code of a voltage in a range of evenly spaced codes, or `None` if the
+/// voltage is outside the range or not on one of its increments
+const fn voltage_code(millivolts: i32, min: i32, max: i32, step: i32, base: u8) -> Option<u8> {
+    if millivolts < min || millivolts > max || (millivolts - min) % step != 0 {
+        return None;
+    }
+
+    Some(base + ((millivolts - min) / step) as u8)
+}
+
+/// The gate driving voltage VGH, set with
+/// [DisplayCommands::set_gate_driving_voltage](crate::command::DisplayCommands::set_gate_driving_voltage).
+///
+/// Valid voltages are 12 V to 20 V in increments of 0.5 V.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct GateVoltage(u8);
+
+impl GateVoltage {
+    /// Create a gate voltage from millivolts.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if the voltage is not valid, see [GateVoltage].
+    pub const fn new(millivolts: u16) -> Self {
+        match Self::from_millivolts(millivolts) {
+            Some(voltage) => voltage,
+            None => panic!("Invalid gate voltage"),
+        }
+    }
+
+    /// Create a gate voltage from millivolts, or `None` if the voltage is not valid
+    pub const fn from_millivolts(millivolts: u16) -> Option<Self> {
+        match voltage_code(millivolts as i32, 12_000, 20_000, 500, 0x07) {
+            Some(code) => Some(GateVoltage(code)),
+            None => None,
+        }
+    }
+
+    /// Returns the voltage in millivolts
+    pub const fn millivolts(&self) -> u16 {
+        12_000 + (self.0 - 0x07) as u16 * 500
+    }
+
+    /// Create a gate voltage from the data byte of the gate driving voltage command, or `None` if
+    /// the byte is not valid
+    pub(crate) const fn from_code(code: u8) -> Option<Self> {
+        if code < 0x07 || code > 0x17 {
+            return None;
+        }
+
+        Some(GateVoltage(code))
+    }
+
+    /// Returns the data byte of the gate driving voltage command
+    pub(crate) const fn code(&self) -> u8 {
+        self.0
+    }
+}
+
+impl Default for GateVoltage {
+    /// Default is the POR value of 20 V
+    fn default() -> Self {
+        GateVoltage::new(20_000)
+    }
+}
+
+/// The source driving voltages VSH1, VSH2 and VSL, set with
+/// [DisplayCommands::set_source_driving_voltage](crate::command::DisplayCommands::set_source_driving_voltage).
+///
+/// Valid voltages are:
+/// - VSH1: 9 V to 17 V in increments of 0.2 V.
+/// - VSH2: 2.4 V to 8.8 V in increments of 0.1 V, and 9 V to 17 V in increments of 0.2 V.
+///   VSH2 must not be larger than VSH1.
+/// - VSL: -9 V to -17 V in increments of 0.5 V.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SourceVoltage {
+    vsh1: u8,
+    vsh2: u8,
+    vsl: u8,
+}
+
+impl SourceVoltage {
+    /// Create the source voltages from millivolts.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if a voltage is not valid, see [SourceVoltage].
+    pub const fn new(vsh1_millivolts: u16, vsh2_millivolts: u16, vsl_millivolts: i16) -> Self {
+        match Self::from_millivolts(vsh1_millivolts, vsh2_millivolts, vsl_millivolts) {
+            Some(voltage) => voltage,
+            None => panic!("Invalid source voltage"),
+        }
+    }
+
+    /// Create the source voltages from millivolts, or `None` if a voltage is not valid
+    pub const fn from_millivolts(
+        vsh1_millivolts: u16,
+        vsh2_millivolts: u16,
+        vsl_millivolts: i16,
+    ) -> Option<Self> {
+        if vsh2_millivolts > vsh1_millivolts {
+            return None;
+        }
+
+        // VSH1 only supports the upper range of the VSH values
+        let vsh1 = match voltage_code(vsh1_millivolts as i32, 9_000, 17_000, 200, 0x23) {
+            Some(code) => code,
+            None => return None,
+        };
+        let vsh2 = match voltage_code(vsh2_millivolts as i32, 2_400, 8_800, 100, 0x8E) {
+            Some(code) => code,
+            None => match voltage_code(vsh2_millivolts as i32, 9_000, 17_000, 200, 0x23) {
+                Some(code) => code,
+                None => return None,
+            },
+        };
+        // The VSL codes go up in steps of two
+        let vsl = match voltage_code(-(vsl_millivolts as i32), 9_000, 17_000, 500, 0x0D) {
+            Some(code) => code * 2,
+            None => return None,
+        };
+
+        Some(SourceVoltage { vsh1, vsh2, vsl })
+    }
+
+    /// Returns the VSH1 voltage in millivolts
+    pub const fn vsh1_millivolts(&self) -> u16 {
+        match code_voltage(self.vsh1, 9_000, 17_000, 200, 0x23) {
+            Some(millivolts) => millivolts as u16,
+            None => 0,
+        }
+    }
+
+    /// Returns the VSH2 voltage in millivolts
+    pub const fn vsh2_millivolts(&self) -> u16 {
+        match code_voltage(self.vsh2, 2_400, 8_800, 100, 0x8E) {
+            Some(millivolts) => millivolts as u16,
+            None => match code_voltage(self.vsh2, 9_000, 17_000, 200, 0x23) {
+                Some(millivolts) => millivolts as u16,
+                None => 0,
+            },
+        }
+    }
+
+    /// Returns the VSL voltage in millivolts
+    pub const fn vsl_millivolts(&self) -> i16 {
+        match code_voltage(self.vsl / 2, 9_000, 17_000, 500, 0x0D) {
+            Some(millivolts) => -millivolts as i16,
+            None => 0,
+        }
+    }
+
+    /// Create the source voltages from the data bytes of the source driving voltage command, or
+    /// `None` if the bytes are not valid
+    pub(crate) const fn from_data(data: [u8; 3]) -> Option<Self> {
+        let voltage = SourceVoltage {
+            vsh1: data[0],
+            vsh2: data[1],
+            vsl: data[2],
+        };
+
+        // Only accept the bytes a valid voltage is sent as
+        match Self::from_millivolts(
+            voltage.vsh1_millivolts(),
+            voltage.vsh2_millivolts(),
+            voltage.vsl_millivolts(),
+        ) {
+            Some(valid)
+                if valid.vsh1 == data[0] && valid.vsh2 == data[1] && valid.vsl == data[2] =>
+            {
+                Some(valid)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the data bytes of the source driving voltage command
+    pub(crate) const fn data(&self) -> [u8; 3] {
+        [self.vsh1, self.vsh2, self.vsl]
+    }
+}
+
+impl Default for SourceVoltage {
+    /// Default is the POR value of VSH1 15 V, VSH2 5 V and VSL -15 V
+    fn default() -> Self {
+        SourceVoltage::new(15_000, 5_000, -15_000)
+    }
+}
+
+/// The VCOM voltage, set with
+/// [DisplayCommands::write_vcom](crate::command::DisplayCommands::write_vcom).
+///
+/// Valid voltages are -0.2 V to -3 V in increments of 0.1 V.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct VcomVoltage(u8);
+
+impl VcomVoltage {
+    /// Create a VCOM voltage from millivolts.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if the voltage is not valid, see [VcomVoltage].
+    pub const fn new(millivolts: i16) -> Self {
+        match Self::from_millivolts(millivolts) {
+            Some(voltage) => voltage,
+            None => panic!("Invalid VCOM voltage"),
+        }
+    }
+
+    /// Create a VCOM voltage from millivolts, or `None` if the voltage is not valid
+    pub const fn from_millivolts(millivolts: i16) -> Option<Self> {
+        // The VCOM codes go up in steps of four
+        match voltage_code(-(millivolts as i32), 200, 3_000, 100, 0x02) {
+            Some(code) => Some(VcomVoltage(code * 4)),
+            None => None,
+        }
+    }
+
+    /// Returns the voltage in millivolts
+    pub const fn millivolts(&self) -> i16 {
+        -((self.0 / 4 - 0x02) as i16 * 100 + 200)
+    }
+
+    /// Create a VCOM voltage from the register value, or `None` if the value is not valid
+    // `is_multiple_of` needs Rust 1.87, keep `%` so older compilers are supported
+    #[allow(clippy::manual_is_multiple_of)]
+    pub(crate) const fn from_code(code: u8) -> Option<Self> {
+        if code % 4 != 0 || code < 0x08 || code > 0x78 {
+            return None;
+        }
+
+        Some(VcomVoltage(code))
+    }
+
+    /// Returns the data byte of the VCOM command
+    pub(crate) const fn code(&self) -> u8 {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gate_voltage_codes() {
+        assert_eq!(GateVoltage::new(12_000).code(), 0x07);
+        assert_eq!(GateVoltage::new(12_500).code(), 0x08);
+        assert_eq!(GateVoltage::new(20_000).code(), 0x17);
+        assert_eq!(GateVoltage::from_millivolts(11_500), None);
+        assert_eq!(GateVoltage::from_millivolts(20_500), None);
+        assert_eq!(GateVoltage::from_millivolts(12_250), None);
+
+        assert_eq!(GateVoltage::from_code(0x06), None);
+        assert_eq!(GateVoltage::from_code(0x18), None);
+        for code in 0x07..=0x17 {
+            let voltage = GateVoltage::from_code(code).unwrap();
+            assert_eq!(
+                GateVoltage::from_millivolts(voltage.millivolts()),
+                Some(voltage)
+            );
+        }
+    }
+
+    #[test]
+    fn source_voltage_codes() {
+        assert_eq!(
+            SourceVoltage::new(9_000, 2_400, -9_000).data(),
+            [0x23, 0x8E, 0x1A]
+        );
+        assert_eq!(
+            SourceVoltage::new(17_000, 8_800, -17_000).data(),
+            [0x4B, 0xCE, 0x3A]
+        );
+        // VSH2 switches to the VSH1 codes from 9 V
+        assert_eq!(
+            SourceVoltage::new(17_000, 9_000, -9_500).data(),
+            [0x4B, 0x23, 0x1C]
+        );
+
+        assert_eq!(SourceVoltage::from_millivolts(8_800, 2_400, -9_000), None);
+        assert_eq!(SourceVoltage::from_millivolts(17_200, 2_400, -9_000), None);
+        assert_eq!(SourceVoltage::from_millivolts(9_100, 2_400, -9_000), None);
+        assert_eq!(SourceVoltage::from_millivolts(9_000, 2_300, -9_000), None);
+        assert_eq!(SourceVoltage::from_millivolts(9_000, 8_900, -9_000), None);
+        assert_eq!(SourceVoltage::from_millivolts(9_000, 9_200, -9_000), None);
+        assert_eq!(SourceVoltage::from_millivolts(9_000, 2_400, -8_500), None);
+        assert_eq!(SourceVoltage::from_millivolts(9_000, 2_400, -17_500), None);
+        assert_eq!(SourceVoltage::from_millivolts(9_000, 2_400, -9_200), None);
+    }
+
+    #[test]
+    fn source_voltage_data_round_trips() {
+        let [vsh1, vsh2, vsl] = SourceVoltage::new(17_000, 5_000, -15_000).data();
+
+        // Every byte a valid voltage is sent as decodes to that voltage, all others are rejected
+        for byte in 0..=u8::MAX {
+            for data in [[byte, vsh2, vsl], [vsh1, byte, vsl], [vsh1, vsh2, byte]] {
+                if let Some(voltage) = SourceVoltage::from_data(data) {
+                    assert_eq!(voltage.data(), data);
+                    assert_eq!(
+                        SourceVoltage::from_millivolts(
+                            voltage.vsh1_millivolts(),
+                            voltage.vsh2_millivolts(),
+                            voltage.vsl_millivolts(),
+                        ),
+                        Some(voltage)
+                    );
+                }
+            }
+        }
+
+        assert_eq!(SourceVoltage::from_data([0x22, vsh2, vsl]), None);
+        assert_eq!(SourceVoltage::from_data([0x4C, vsh2, vsl]), None);
+        assert_eq!(SourceVoltage::from_data([vsh1, 0x8D, vsl]), None);
+        assert_eq!(SourceVoltage::from_data([vsh1, 0xCF, vsl]), None);
+        assert_eq!(SourceVoltage::from_data([vsh1, vsh2, 0x1B]), None);
+        assert_eq!(SourceVoltage::from_data([vsh1, vsh2, 0x3C]), None);
+    }
+
+    #[test]
+    fn vcom_voltage_codes() {
+        assert_eq!(VcomVoltage::new(-200).code(), 0x08);
+        assert_eq!(VcomVoltage::new(-300).code(), 0x0C);
+        assert_eq!(VcomVoltage::new(-3_000).code(), 0x78);
+        assert_eq!(VcomVoltage::from_millivolts(-100), None);
+        assert_eq!(VcomVoltage::from_millivolts(-3_100), None);
+        assert_eq!(VcomVoltage::from_millivolts(-250), None);
+
+        assert_eq!(VcomVoltage::from_code(0x04), None);
+        assert_eq!(VcomVoltage::from_code(0x7C), None);
+        assert_eq!(VcomVoltage::from_code(0x09), None);
+        for code in (0x08..=0x78).step_by(4) {
+            let voltage = VcomVoltage::from_code(code).unwrap();
+            assert_eq!(
+                VcomVoltage::from_millivolts(voltage.millivolts()),
+                Some(voltage)
+            );
+        }
+    }
+}